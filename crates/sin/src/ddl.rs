@@ -0,0 +1,466 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::Result;
+
+use crate::{schema_module, DbFields, Errors};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ClusteringOrder {
+    Asc,
+    Desc,
+}
+
+impl ClusteringOrder {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+
+    fn to_schema(self) -> TokenStream {
+        let schema = schema_module();
+        match self {
+            Self::Asc => quote! { #schema::ClusteringOrder::Asc },
+            Self::Desc => quote! { #schema::ClusteringOrder::Desc },
+        }
+    }
+}
+
+// table options accepted by the nosql attribute, only used for the generated DDL
+#[derive(Debug, Default)]
+pub(crate) struct TableOptions {
+    pub(crate) clustering_order: Vec<(syn::Ident, ClusteringOrder)>,
+    pub(crate) compaction: Option<syn::Ident>,
+    pub(crate) default_ttl: Option<u32>,
+    pub(crate) comment: Option<String>,
+}
+
+impl TableOptions {
+    // clustering_order = [status = desc, created_at = asc]
+    pub(crate) fn parse_clustering_order(
+        input: ParseStream<'_>,
+    ) -> Result<Vec<(syn::Ident, ClusteringOrder)>> {
+        input
+            .parse::<syn::ExprArray>()?
+            .elems
+            .into_iter()
+            .map(|e| {
+                let syn::Expr::Assign(ref assign) = e else {
+                    return Err(syn::Error::new_spanned(
+                        &e,
+                        "expected `column = asc | desc`",
+                    ));
+                };
+                let column = expr_ident(&assign.left)?;
+                let order = expr_ident(&assign.right)?;
+                let order = match order.to_string().as_str() {
+                    "asc" => ClusteringOrder::Asc,
+                    "desc" => ClusteringOrder::Desc,
+                    _ => return Err(syn::Error::new(order.span(), "expected `asc` or `desc`")),
+                };
+                Ok((column, order))
+            })
+            .collect()
+    }
+
+    // clustering order can only be defined on clustering keys
+    pub(crate) fn validate(&self, fields: &DbFields, errors: &mut Errors) {
+        for (index, (column, _)) in self.clustering_order.iter().enumerate() {
+            if !fields
+                .clustering_keys
+                .iter()
+                .any(|key| key.ident == *column)
+            {
+                errors.push(syn::Error::new(
+                    column.span(),
+                    format!("`{}` is not a clustering key", column),
+                ));
+            } else if self
+                .clustering_order
+                .iter()
+                .take(index)
+                .any(|(seen, _)| seen == column)
+            {
+                errors.push(syn::Error::new(
+                    column.span(),
+                    format!("duplicate clustering order for `{}`", column),
+                ));
+            }
+        }
+    }
+
+    pub(crate) fn order_of(&self, ident: &syn::Ident) -> ClusteringOrder {
+        self.clustering_order
+            .iter()
+            .find(|(column, _)| column == ident)
+            .map(|(_, order)| *order)
+            .unwrap_or(ClusteringOrder::Asc)
+    }
+}
+
+fn expr_ident(expr: &syn::Expr) -> Result<syn::Ident> {
+    match expr {
+        syn::Expr::Path(path) => path
+            .path
+            .get_ident()
+            .cloned()
+            .ok_or_else(|| syn::Error::new_spanned(expr, "expected an identifier")),
+        _ => Err(syn::Error::new_spanned(expr, "expected an identifier")),
+    }
+}
+
+fn type_args(arguments: &syn::PathArguments) -> Vec<&syn::Type> {
+    match arguments {
+        syn::PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_u8(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(path) if path.path.is_ident("u8"))
+}
+
+// nested collections have to be frozen in cql
+fn element_type(ty: &syn::Type) -> Option<String> {
+    let cql_type = cql_type(ty)?;
+    if cql_type.contains('<') {
+        return Some(format!("frozen<{}>", cql_type));
+    }
+    Some(cql_type)
+}
+
+// modules a known type can be referred to through, e.g. `std::collections::HashMap`
+fn known_modules(ident: &str) -> &'static [&'static str] {
+    match ident {
+        "String" => &["std::string", "alloc::string"],
+        "Option" => &["std::option", "core::option"],
+        "Vec" => &["std::vec", "alloc::vec"],
+        "HashMap" => &["std::collections", "std::collections::hash_map"],
+        "HashSet" => &["std::collections", "std::collections::hash_set"],
+        "BTreeMap" => &[
+            "std::collections",
+            "alloc::collections",
+            "std::collections::btree_map",
+        ],
+        "BTreeSet" => &[
+            "std::collections",
+            "alloc::collections",
+            "std::collections::btree_set",
+        ],
+        "OffsetDateTime" => &["time"],
+        "Uuid" => &["uuid"],
+        _ => &[],
+    }
+}
+
+// maps rust types onto cql types, `None` if the type can't be inferred and
+// needs an explicit `#[sin(cql_type = "..")]`, which is the case for user types
+// and for known type names behind any other module
+pub(crate) fn cql_type(ty: &syn::Type) -> Option<String> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    if path.qself.is_some() {
+        return None;
+    }
+    let segment = path.path.segments.last()?;
+    let ident = segment.ident.to_string();
+    let modules = path.path.segments.len() - 1;
+    if modules > 0 {
+        let module = path
+            .path
+            .segments
+            .iter()
+            .take(modules)
+            .map(|module| match module.arguments {
+                syn::PathArguments::None => Some(module.ident.to_string()),
+                _ => None,
+            })
+            .collect::<Option<Vec<String>>>()?
+            .join("::");
+        if !known_modules(&ident).contains(&module.as_str()) {
+            return None;
+        }
+    }
+    let args = type_args(&segment.arguments);
+
+    let cql_type = match (ident.as_str(), args.as_slice()) {
+        ("i8", []) => "tinyint".to_string(),
+        ("i16", []) => "smallint".to_string(),
+        ("i32", []) => "int".to_string(),
+        ("i64", []) => "bigint".to_string(),
+        ("f32", []) => "float".to_string(),
+        ("f64", []) => "double".to_string(),
+        ("bool", []) => "boolean".to_string(),
+        ("String", []) => "text".to_string(),
+        ("OffsetDateTime", []) => "timestamp".to_string(),
+        ("Uuid", []) => "uuid".to_string(),
+        ("Option", [inner]) => cql_type(inner)?,
+        ("Vec", [inner]) if is_u8(inner) => "blob".to_string(),
+        ("Vec", [inner]) => format!("list<{}>", element_type(inner)?),
+        ("HashSet" | "BTreeSet", [inner]) => format!("set<{}>", element_type(inner)?),
+        ("HashMap" | "BTreeMap", [key, value]) => {
+            format!("map<{}, {}>", element_type(key)?, element_type(value)?)
+        }
+        _ => return None,
+    };
+    Some(cql_type)
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+// fails with the first column whose cql type can't be inferred, the error is
+// returned by `create_table_statement` instead of failing the whole model
+pub(crate) fn generate_create_table<'f>(
+    table: &str,
+    keyspace: &str,
+    fields: &'f DbFields,
+    options: &TableOptions,
+) -> std::result::Result<String, &'f str> {
+    let columns = fields
+        .fields
+        .iter()
+        .map(|f| {
+            let cql_type = f.cql_type.as_deref().ok_or(f.column.as_str())?;
+            Ok(match f.is_static {
+                true => format!("{} {} STATIC", f.column, cql_type),
+                false => format!("{} {}", f.column, cql_type),
+            })
+        })
+        .collect::<std::result::Result<Vec<String>, &str>>()?
+        .join(", ");
+
+    let partition_keys = fields
+        .partition_keys
+        .iter()
+        .map(|f| f.column.as_str())
+        .collect::<Vec<&str>>()
+        .join(", ");
+
+    let mut primary_key = format!("({})", partition_keys);
+    for key in fields.clustering_keys.iter() {
+        primary_key.extend([", ", key.column.as_str()]);
+    }
+
+    let mut table_options = Vec::new();
+    if !options.clustering_order.is_empty() {
+        let order = fields
+            .clustering_keys
+            .iter()
+            .map(|key| format!("{} {}", key.column, options.order_of(&key.ident).as_str()))
+            .collect::<Vec<String>>()
+            .join(", ");
+        table_options.push(format!("CLUSTERING ORDER BY ({})", order));
+    }
+    if let Some(compaction) = &options.compaction {
+        table_options.push(format!("compaction = {{'class': '{}'}}", compaction));
+    }
+    if let Some(ttl) = options.default_ttl {
+        table_options.push(format!("default_time_to_live = {}", ttl));
+    }
+    if let Some(comment) = &options.comment {
+        table_options.push(format!("comment = '{}'", escape(comment)));
+    }
+
+    let mut statement = format!(
+        "CREATE TABLE IF NOT EXISTS {}.{} ({}, PRIMARY KEY ({}))",
+        keyspace, table, columns, primary_key
+    );
+    if !table_options.is_empty() {
+        statement.extend([" WITH ".to_string(), table_options.join(" AND ")]);
+    }
+    Ok(statement)
+}
+
+pub(crate) fn generate_schema(
+    table: &str,
+    keyspace: &str,
+    fields: &DbFields,
+    options: &TableOptions,
+) -> TokenStream {
+    let schema = schema_module();
+    let columns = fields.fields.iter().map(|f| {
+        let partition_position = fields
+            .partition_keys
+            .iter()
+            .position(|key| key.ident == f.ident);
+        let clustering_position = fields
+            .clustering_keys
+            .iter()
+            .position(|key| key.ident == f.ident);
+
+        let (kind, position, order) = match (partition_position, clustering_position) {
+            (Some(position), _) => (
+                quote! { #schema::ColumnKind::PartitionKey },
+                quote! { Some(#position) },
+                quote! { None },
+            ),
+            (_, Some(position)) => {
+                let order = options.order_of(&f.ident).to_schema();
+                (
+                    quote! { #schema::ColumnKind::ClusteringKey },
+                    quote! { Some(#position) },
+                    quote! { Some(#order) },
+                )
+            }
+            _ if f.is_static => (
+                quote! { #schema::ColumnKind::Static },
+                quote! { None },
+                quote! { None },
+            ),
+            _ => (
+                quote! { #schema::ColumnKind::Regular },
+                quote! { None },
+                quote! { None },
+            ),
+        };
+        let column = &f.column;
+        let cql_type = match &f.cql_type {
+            Some(cql_type) => quote! { Some(#cql_type) },
+            None => quote! { None },
+        };
+
+        quote! {
+            #schema::ColumnSchema {
+                name: #column,
+                cql_type: #cql_type,
+                kind: #kind,
+                position: #position,
+                order: #order,
+            }
+        }
+    });
+
+    quote! {
+        static SCHEMA: #schema::TableSchema = #schema::TableSchema {
+            keyspace: #keyspace,
+            table: #table,
+            columns: &[#(#columns),*],
+        };
+        &SCHEMA
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::NoSqlField;
+    use std::rc::Rc;
+
+    fn cql(ty: &str) -> Option<String> {
+        syn::parse_str::<syn::Type>(ty)
+            .ok()
+            .and_then(|ty| cql_type(&ty))
+    }
+
+    fn field(column: &str, ty: &str, is_static: bool) -> Result<Rc<NoSqlField>> {
+        let ty = syn::parse_str::<syn::Type>(ty)?;
+        Ok(Rc::new(NoSqlField {
+            ident: syn::Ident::new(column, proc_macro2::Span::call_site()),
+            cql_type: cql_type(&ty),
+            ty,
+            vis: syn::Visibility::Inherited,
+            column: column.to_string(),
+            is_static,
+        }))
+    }
+
+    fn events(fields: Vec<Rc<NoSqlField>>) -> DbFields {
+        let keys = |columns: &[&str]| {
+            fields
+                .iter()
+                .filter(|f| columns.contains(&f.column.as_str()))
+                .cloned()
+                .collect()
+        };
+        DbFields {
+            partition_keys: keys(&["id", "day"]),
+            clustering_keys: keys(&["created", "seq"]),
+            fields,
+        }
+    }
+
+    #[test]
+    fn maps_rust_types() {
+        assert_eq!(cql("i64").as_deref(), Some("bigint"));
+        assert_eq!(cql("Option<i16>").as_deref(), Some("smallint"));
+        assert_eq!(cql("std::string::String").as_deref(), Some("text"));
+        assert_eq!(cql("time::OffsetDateTime").as_deref(), Some("timestamp"));
+        assert_eq!(cql("uuid::Uuid").as_deref(), Some("uuid"));
+        assert_eq!(cql("Vec<u8>").as_deref(), Some("blob"));
+        assert_eq!(
+            cql("HashMap<String, Vec<i32>>").as_deref(),
+            Some("map<text, frozen<list<int>>>")
+        );
+        assert_eq!(
+            cql("std::collections::BTreeSet<bool>").as_deref(),
+            Some("set<boolean>")
+        );
+    }
+
+    #[test]
+    fn needs_cql_type_for_user_types() {
+        assert_eq!(cql("Status"), None);
+        assert_eq!(cql("my::String"), None);
+        assert_eq!(cql("models::Uuid"), None);
+        assert_eq!(cql("Vec<Status>"), None);
+        assert_eq!(cql("(i64, i64)"), None);
+    }
+
+    #[test]
+    fn creates_table_with_options() -> Result<()> {
+        let fields = events(vec![
+            field("id", "i64", false)?,
+            field("day", "String", false)?,
+            field("created", "OffsetDateTime", false)?,
+            field("seq", "i64", false)?,
+            field("owner", "String", true)?,
+        ]);
+        let options = TableOptions {
+            clustering_order: vec![(
+                syn::Ident::new("seq", proc_macro2::Span::call_site()),
+                ClusteringOrder::Desc,
+            )],
+            compaction: None,
+            default_ttl: Some(3600),
+            comment: Some("it's events".to_string()),
+        };
+
+        assert_eq!(
+            generate_create_table("events", "test", &fields, &options).as_deref(),
+            Ok(
+                "CREATE TABLE IF NOT EXISTS test.events (id bigint, day text, created timestamp, \
+             seq bigint, owner text STATIC, PRIMARY KEY ((id, day), created, seq)) \
+             WITH CLUSTERING ORDER BY (created ASC, seq DESC) AND default_time_to_live = 3600 \
+             AND comment = 'it''s events'"
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fails_create_table_without_cql_type() -> Result<()> {
+        let fields = events(vec![
+            field("id", "i64", false)?,
+            field("status", "Status", false)?,
+        ]);
+
+        assert_eq!(
+            generate_create_table("events", "test", &fields, &TableOptions::default()),
+            Err("status")
+        );
+        Ok(())
+    }
+}
@@ -1,104 +1,238 @@
 use proc_macro2::TokenStream;
 use quote::{quote, quote_spanned};
 use syn::spanned::Spanned;
-use syn::{
-    parse_macro_input, parse_quote, Data, DeriveInput, Fields, FieldsNamed, GenericParam, Generics,
-};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, FieldsNamed};
 
 use proc_macro2::Span;
 use quote::ToTokens;
 use syn::parse::ParseStream;
 use syn::Result;
 
+mod ddl;
+use ddl::TableOptions;
+
 #[derive(Debug)]
 struct DbFields {
     fields: Vec<Rc<NoSqlField>>,
-    partition_keys: Vec<FieldRef>,
-    clustering_keys: Option<Vec<FieldRef>>,
+    partition_keys: Vec<Rc<NoSqlField>>,
+    clustering_keys: Vec<Rc<NoSqlField>>,
 }
 #[derive(Debug)]
 struct NoSqlField {
     // TODO : make this string wrapped in quotes when creating a query
     ident: syn::Ident,
     ty: syn::Type,
+    vis: syn::Visibility,
+    column: String,
+    // `None` when it can't be inferred, which only fails the DDL of the model
+    cql_type: Option<String>,
+    is_static: bool,
     //span : proc_macro2::Span,
 }
 
-#[derive(Debug)]
-struct FieldRef {
-    name: String,
-    index: Option<Rc<NoSqlField>>,
+// field level `#[sin(...)]` attributes
+#[derive(Debug, Default)]
+struct FieldAttrs {
+    rename: Option<String>,
+    skip: bool,
+    default: bool,
+    cql_type: Option<String>,
+    is_static: bool,
 }
 
-impl From<String> for FieldRef {
-    fn from(value: String) -> Self {
-        Self {
-            name: value,
-            index: None,
+impl FieldAttrs {
+    fn from_attrs(attrs: &[syn::Attribute]) -> Result<Self> {
+        let mut field_attrs = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("sin")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.rename = Some(value.value());
+                } else if meta.path.is_ident("skip") {
+                    field_attrs.skip = true;
+                } else if meta.path.is_ident("default") {
+                    field_attrs.default = true;
+                } else if meta.path.is_ident("static") {
+                    field_attrs.is_static = true;
+                } else if meta.path.is_ident("cql_type") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.cql_type = Some(value.value());
+                } else {
+                    return Err(meta.error("unknown sin field attribute"));
+                }
+                Ok(())
+            })?;
         }
+        Ok(field_attrs)
+    }
+
+    fn column(&self, ident: &syn::Ident) -> String {
+        self.rename.clone().unwrap_or_else(|| ident.to_string())
     }
 }
 
 use std::rc::Rc;
 
-fn get_field_with_types<'a>(
-    data: &'a Data,
-    mut partition_keys: Vec<FieldRef>,
-    mut clustering_keys: Option<Vec<FieldRef>>,
-) -> Option<DbFields> {
+fn get_field_with_types(
+    name: &syn::Ident,
+    data: &Data,
+    partition_keys: &[syn::Ident],
+    clustering_keys: &[syn::Ident],
+) -> Result<DbFields> {
     match *data {
         Data::Struct(ref data) => {
             if let Fields::Named(ref fields) = data.fields {
                 let mut db_fields = Vec::with_capacity(fields.named.len());
-                let _: Vec<()> = fields
-                    .named
-                    .iter()
-                    .map(|f| {
-                        f.ident.as_ref().map(|ident| {
-                            let entry = Rc::new(NoSqlField {
-                                ident: ident.clone(),
-                                ty: f.ty.clone(),
-                            });
-                            db_fields.push(entry.clone());
-
-                            let index = db_fields.len() - 1;
-
-                            partition_keys
-                                .iter_mut()
-                                .find(|item| db_fields[index].ident == item.name)
-                                .map(|found| found.index = Some(entry.clone()));
-
-                            clustering_keys.as_deref_mut().map(|cluster_keys| {
-                                cluster_keys
-                                    .iter_mut()
-                                    .find(|item| db_fields[index].ident == item.name)
-                                    .map(|found| found.index = Some(entry.clone()))
-                            });
-                        });
-                    })
-                    .collect();
-                return Some(DbFields {
+                let mut errors = Errors::default();
+                for f in fields.named.iter() {
+                    let attrs = match FieldAttrs::from_attrs(&f.attrs) {
+                        Ok(attrs) => attrs,
+                        Err(error) => {
+                            errors.push(error);
+                            continue;
+                        }
+                    };
+                    if attrs.skip {
+                        continue;
+                    }
+                    let cql_type = attrs.cql_type.clone().or_else(|| ddl::cql_type(&f.ty));
+                    if let Some(ident) = f.ident.as_ref() {
+                        db_fields.push(Rc::new(NoSqlField {
+                            ident: ident.clone(),
+                            ty: f.ty.clone(),
+                            vis: f.vis.clone(),
+                            column: attrs.column(ident),
+                            cql_type,
+                            is_static: attrs.is_static,
+                        }));
+                    }
+                }
+
+                let mut seen = Vec::with_capacity(partition_keys.len() + clustering_keys.len());
+                let partition_keys = resolve_keys(
+                    name,
+                    &db_fields,
+                    partition_keys,
+                    true,
+                    &mut seen,
+                    &mut errors,
+                );
+                let clustering_keys = resolve_keys(
+                    name,
+                    &db_fields,
+                    clustering_keys,
+                    false,
+                    &mut seen,
+                    &mut errors,
+                );
+
+                if clustering_keys.is_empty() {
+                    for field in db_fields.iter().filter(|f| f.is_static) {
+                        errors.push(syn::Error::new(
+                            field.ident.span(),
+                            "static columns need at least one clustering key",
+                        ));
+                    }
+                }
+
+                return errors.finish(DbFields {
                     fields: db_fields,
                     partition_keys,
                     clustering_keys,
                 });
             }
-            None
+            Err(syn::Error::new(
+                Span::call_site(),
+                "expected struct with named fields",
+            ))
+        }
+        _ => Err(syn::Error::new(
+            Span::call_site(),
+            "expected struct with named fields",
+        )),
+    }
+}
+
+// collects every error instead of bailing out on the first one
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, error: syn::Error) {
+        match self.0.as_mut() {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+
+    fn check<T>(&mut self, result: Result<T>) -> Option<T> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    fn finish<T>(self, value: T) -> Result<T> {
+        match self.0 {
+            Some(errors) => Err(errors),
+            None => Ok(value),
         }
-        _ => None,
+    }
+
+    fn into_error(self) -> syn::Error {
+        self.0
+            .unwrap_or_else(|| syn::Error::new(Span::call_site(), "invalid nosql model"))
     }
 }
 
-#[proc_macro_derive(ToCqlData)]
+// maps key idents from the nosql attribute onto the struct fields, `seen` tracks
+// keys across partition and clustering keys to reject duplicates
+fn resolve_keys(
+    name: &syn::Ident,
+    fields: &[Rc<NoSqlField>],
+    keys: &[syn::Ident],
+    is_partition: bool,
+    seen: &mut Vec<(syn::Ident, bool)>,
+    errors: &mut Errors,
+) -> Vec<Rc<NoSqlField>> {
+    let mut resolved = Vec::with_capacity(keys.len());
+
+    for key in keys {
+        if let Some((_, seen_as_partition)) = seen.iter().find(|(seen, _)| seen == key) {
+            let message = if *seen_as_partition && !is_partition {
+                format!("`{}` can't be both a partition and a clustering key", key)
+            } else {
+                format!("duplicate key `{}`", key)
+            };
+            errors.push(syn::Error::new(key.span(), message));
+            continue;
+        }
+        seen.push((key.clone(), is_partition));
+
+        match fields.iter().find(|f| f.ident == *key) {
+            Some(field) if field.is_static => errors.push(syn::Error::new(
+                key.span(),
+                format!("static column `{}` can't be a key", key),
+            )),
+            Some(field) => resolved.push(field.clone()),
+            None => errors.push(syn::Error::new(
+                key.span(),
+                format!("`{}` is not a column of `{}`", key, name),
+            )),
+        }
+    }
+    resolved
+}
+
+#[proc_macro_derive(ToCqlData, attributes(sin))]
 pub fn derive_to_cql(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let name = input.ident;
+    let types = types_module();
     match generate_derive_body(&input.data) {
         Ok(derive_body) => {
             let expanded = quote! {
-                impl ToCqlData for #name{
-                    fn to_cql(self) -> CqlType{
+                impl #types::ToCqlData for #name{
+                    fn to_cql(self) -> #types::CqlType{
                         #derive_body
                     }
                 }
@@ -110,22 +244,30 @@ pub fn derive_to_cql(input: proc_macro::TokenStream) -> proc_macro::TokenStream
 }
 
 fn generate_derive_body(data: &Data) -> std::result::Result<TokenStream, TokenStream> {
+    let types = types_module();
     match *data {
         Data::Struct(ref data) => match data.fields {
             Fields::Named(ref fields) => {
                 let capacity = fields.named.len();
-                let field_itr = fields.named.iter().map(|f| {
-                    let name = &f.ident;
-                    quote_spanned! {
+                let mut field_itr = Vec::with_capacity(capacity);
+                for f in fields.named.iter() {
+                    let attrs =
+                        FieldAttrs::from_attrs(&f.attrs).map_err(|e| e.to_compile_error())?;
+                    let (Some(name), false) = (&f.ident, attrs.skip) else {
+                        continue;
+                    };
+                    let column = attrs.column(name);
+                    field_itr.push(quote_spanned! {
                         f.span() =>
-                            let value = ToCqlData::to_cql(self.#name);
-                            res.insert(stringify!(#name).to_string(), value);
-                    }
-                });
+                            let value = #types::ToCqlData::to_cql(self.#name);
+                            res.insert(#column.to_string(), value);
+                    });
+                }
                 Ok(quote! {
-                    let mut res : HashMap<String, CqlType> = HashMap::with_capacity(#capacity);
+                    let mut res : ::std::collections::HashMap<String, #types::CqlType> =
+                        ::std::collections::HashMap::with_capacity(#capacity);
                     #(#field_itr)*
-                    CqlType::Row(res)
+                    #types::CqlType::Row(res)
                 })
             }
             _ => Err(
@@ -137,7 +279,7 @@ fn generate_derive_body(data: &Data) -> std::result::Result<TokenStream, TokenSt
     }
 }
 
-fn get_fields<'a>(data: &'a Data) -> Option<&'a FieldsNamed> {
+fn get_fields(data: &Data) -> Option<&FieldsNamed> {
     match *data {
         Data::Struct(ref data) => {
             if let Fields::Named(ref fields) = data.fields {
@@ -149,7 +291,7 @@ fn get_fields<'a>(data: &'a Data) -> Option<&'a FieldsNamed> {
     }
 }
 
-#[proc_macro_derive(FromCqlData)]
+#[proc_macro_derive(FromCqlData, attributes(sin))]
 pub fn derive_from_cql(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input: DeriveInput = parse_macro_input!(input);
     let name = input.ident;
@@ -164,23 +306,27 @@ pub fn derive_from_cql(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
         }
     };
 
-    let try_from = try_from_struct(fields);
+    let try_from = match try_from_struct(fields) {
+        Ok(try_from) => try_from,
+        Err(error) => return proc_macro::TokenStream::from(error.to_compile_error()),
+    };
     let from_cql = from_cql_body();
+    let types = types_module();
     let expanded = quote! {
 
-        impl TryFrom<&HashMap<String, CqlType>> for #name{
+        impl TryFrom<&::std::collections::HashMap<String, #types::CqlType>> for #name{
             type Error = ();
-            fn try_from(map: &HashMap<String, CqlType>) -> Result<Self, Self::Error>{
+            fn try_from(map: &::std::collections::HashMap<String, #types::CqlType>) -> Result<Self, Self::Error>{
                 Ok(Self{
                     #try_from
                 })
             }
         }
 
-        impl FromCqlData for #name{
+        impl #types::FromCqlData for #name{
             type Error = String;
 
-            fn from_cql(result : &CqlType) -> Result<Self, Self::Error>{
+            fn from_cql(result : &#types::CqlType) -> Result<Self, Self::Error>{
                 #from_cql
             }
         }
@@ -189,26 +335,50 @@ pub fn derive_from_cql(input: proc_macro::TokenStream) -> proc_macro::TokenStrea
     proc_macro::TokenStream::from(expanded)
 }
 
-fn try_from_struct(fields: &FieldsNamed) -> TokenStream {
-    let expanded = fields.named.iter().map(|f| {
-        let name = &f.ident;
-        quote_spanned! {
-            f.span() =>
-                #name : {
-                    let value = map.get(stringify!(#name)).ok_or(())?;
-                    FromCqlData::from_cql(value)?
-                },
-        }
-    });
-    quote! {
-        #(#expanded)*
+fn try_from_struct(fields: &FieldsNamed) -> Result<TokenStream> {
+    let types = types_module();
+    let mut expanded = Vec::with_capacity(fields.named.len());
+    for f in fields.named.iter() {
+        let attrs = FieldAttrs::from_attrs(&f.attrs)?;
+        let Some(name) = &f.ident else {
+            continue;
+        };
+        let column = attrs.column(name);
+
+        let field = if attrs.skip {
+            quote_spanned! {
+                f.span() =>
+                    #name : Default::default(),
+            }
+        } else if attrs.default {
+            quote_spanned! {
+                f.span() =>
+                    #name : match map.get(#column) {
+                        None | Some(#types::CqlType::Null) => Default::default(),
+                        Some(value) => #types::FromCqlData::from_cql(value)?,
+                    },
+            }
+        } else {
+            quote_spanned! {
+                f.span() =>
+                    #name : {
+                        let value = map.get(#column).ok_or(())?;
+                        #types::FromCqlData::from_cql(value)?
+                    },
+            }
+        };
+        expanded.push(field);
     }
+    Ok(quote! {
+        #(#expanded)*
+    })
 }
 
 fn from_cql_body() -> TokenStream {
+    let types = types_module();
     quote! {
         match result {
-            CqlType::Row(r) => {
+            #types::CqlType::Row(r) => {
                 r.try_into().map_err(|_e| "type mismatch".to_string())
             },
             _ => Err("only expecting row variant".to_string())
@@ -216,107 +386,279 @@ fn from_cql_body() -> TokenStream {
     }
 }
 
-#[proc_macro_derive(Gen)]
-pub fn derive_gen(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+// items the generated row encoding refers to
+fn row_module() -> TokenStream {
+    quote! { ::traits::clients::scylla_cql::row }
+}
+
+// modules of the other items the generated code refers to, models don't have to import them
+fn types_module() -> TokenStream {
+    quote! { ::traits::data_types::types }
+}
+
+fn client_module() -> TokenStream {
+    quote! { ::traits::query::client }
+}
+
+fn column_module() -> TokenStream {
+    quote! { ::traits::query::column }
+}
+
+fn schema_module() -> TokenStream {
+    quote! { ::traits::nosql::schema }
+}
+
+fn relation() -> TokenStream {
+    let client = client_module();
+    quote! { #client::Relation }
+}
+
+// a field of a struct encoded as a row, looked up by its column name
+struct RowField<'a> {
+    ident: &'a syn::Ident,
+    ty: &'a syn::Type,
+    column: String,
+    attrs: FieldAttrs,
+}
+
+fn row_fields(data: &Data) -> Result<Vec<RowField<'_>>> {
+    let fields = get_fields(data)
+        .ok_or_else(|| syn::Error::new(Span::call_site(), "expected struct with named fields"))?;
+    let mut row_fields = Vec::with_capacity(fields.named.len());
+    for f in fields.named.iter() {
+        let attrs = FieldAttrs::from_attrs(&f.attrs)?;
+        let Some(ident) = &f.ident else {
+            continue;
+        };
+        row_fields.push(RowField {
+            ident,
+            ty: &f.ty,
+            column: attrs.column(ident),
+            attrs,
+        });
+    }
+    Ok(row_fields)
+}
+
+#[proc_macro_derive(SerializeRow, attributes(sin))]
+pub fn derive_serialize_row(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    let fields = match row_fields(&input.data) {
+        Ok(fields) => fields,
+        Err(error) => return proc_macro::TokenStream::from(error.to_compile_error()),
+    };
+    let row = row_module();
+    let columns = fields
+        .iter()
+        .filter(|f| !f.attrs.skip)
+        .map(|f| {
+            let ident = f.ident;
+            let value = quote_spanned! {
+                f.ty.span() =>
+                    #row::SerializeValue::serialize(&self.#ident, typ, writer.make_cell_writer())
+            };
+            (f.column.clone(), value)
+        })
+        .collect();
+    proc_macro::TokenStream::from(serialize_row(&input.ident, columns))
+}
 
-    let name = input.ident;
+// `SerializeColumns` and `SerializeRow` of a struct from the value expression of every
+// column, bind markers are matched by name so the columns can be in any order
+fn serialize_row(name: &syn::Ident, columns: Vec<(String, TokenStream)>) -> TokenStream {
+    let row = row_module();
+    let is_empty = columns.is_empty();
+    let arms = columns.iter().map(|(column, value)| {
+        quote! {
+            #column => #value,
+        }
+    });
+    quote! {
+        impl #row::SerializeColumns for #name {
+            fn serialize_column(
+                &self,
+                column: &str,
+                typ: &#row::ColumnType<'_>,
+                writer: &mut #row::RowWriter<'_>,
+            ) -> Option<Result<(), #row::SerializationError>> {
+                let written = match column {
+                    #(#arms)*
+                    _ => return None,
+                };
+                Some(written.map(|_proof| ()))
+            }
 
-    let generics = gen_trait_bounds(input.generics);
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+            fn is_empty(&self) -> bool {
+                #is_empty
+            }
+        }
 
-    let bind_body = generate_body(&input.data);
+        impl #row::SerializeRow for #name {
+            fn serialize(
+                &self,
+                ctx: &#row::RowSerializationContext<'_>,
+                writer: &mut #row::RowWriter<'_>,
+            ) -> Result<(), #row::SerializationError> {
+                #row::SerializeRow::serialize(&#row::Columns(self), ctx, writer)
+            }
 
-    //panic!("{}", bind_body.to_string());
-    let expanded = quote! {
-        impl #impl_generics Gen for #name #ty_generics #where_clause{
-            fn bind_insert_statement(&self, s : &mut Statement){
-                #bind_body
+            fn is_empty(&self) -> bool {
+                #is_empty
             }
         }
-    };
-    proc_macro::TokenStream::from(expanded)
+    }
 }
 
-fn generate_body(data: &Data) -> TokenStream {
-    match *data {
-        Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => {
-                let field_itr = fields.named.iter().map(|f| {
-                    let name = &f.ident;
-                    quote_spanned! {f.span() =>
-                        let value = BindType::bind_the_type(&self.#name);
-                        s.bind_by_name(stringify!(#name), value);
+#[proc_macro_derive(DeserializeRow, attributes(sin))]
+pub fn derive_deserialize_row(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let fields = match row_fields(&input.data) {
+        Ok(fields) => fields,
+        Err(error) => return proc_macro::TokenStream::from(error.to_compile_error()),
+    };
+    let columns: Vec<&RowField<'_>> = fields.iter().filter(|f| !f.attrs.skip).collect();
+    let row = row_module();
+
+    let type_checks = columns.iter().map(|f| {
+        let (ty, column) = (f.ty, &f.column);
+        quote_spanned! {
+            f.ty.span() =>
+                #column => <#ty as #row::DeserializeValue<'frame, 'metadata>>::type_check(spec.typ())?,
+        }
+    });
+    // values are kept in locals that can't clash with the names used below
+    let value = |f: &RowField<'_>| quote::format_ident!("value_{}", f.ident);
+    let locals = columns.iter().map(|f| {
+        let (ty, value) = (f.ty, value(f));
+        quote! {
+            let mut #value: Option<#ty> = None;
+        }
+    });
+    let reads = columns.iter().map(|f| {
+        let (column, value) = (&f.column, value(f));
+        // null cells of `default` fields are read as `None`
+        let read = if f.attrs.default {
+            quote! { #row::DeserializeValue::deserialize(column.spec.typ(), column.slice)? }
+        } else {
+            quote! { Some(#row::DeserializeValue::deserialize(column.spec.typ(), column.slice)?) }
+        };
+        quote_spanned! {
+            f.ty.span() =>
+                #column => #value = #read,
+        }
+    });
+    let init = fields.iter().map(|f| {
+        let (ident, column) = (f.ident, &f.column);
+        if f.attrs.skip {
+            quote! { #ident: Default::default(), }
+        } else if f.attrs.default {
+            let value = value(f);
+            quote! { #ident: #value.unwrap_or_default(), }
+        } else {
+            let value = value(f);
+            quote! { #ident: #value.ok_or_else(|| #row::missing_column(#column))?, }
+        }
+    });
+
+    let expanded = quote! {
+        impl<'frame, 'metadata> #row::DeserializeRow<'frame, 'metadata> for #name {
+            fn type_check(specs: &[#row::ColumnSpec<'_>]) -> Result<(), #row::TypeCheckError> {
+                for spec in specs {
+                    match spec.name() {
+                        #(#type_checks)*
+                        _ => {}
                     }
-                });
-                quote! {
-                    #(#field_itr)*
                 }
+                Ok(())
             }
-            _ => unimplemented!(),
-        },
-        _ => unimplemented!(),
-    }
-}
 
-fn gen_trait_bounds(mut generics: Generics) -> Generics {
-    for param in &mut generics.params {
-        if let GenericParam::Type(ref mut type_param) = *param {
-            type_param.bounds.push(parse_quote!(BindType));
+            fn deserialize(
+                columns: #row::ColumnIterator<'frame, 'metadata>,
+            ) -> Result<Self, #row::DeserializationError> {
+                #(#locals)*
+                for column in columns {
+                    let column = column?;
+                    match column.spec.name() {
+                        #(#reads)*
+                        _ => {}
+                    }
+                }
+                Ok(Self {
+                    #(#init)*
+                })
+            }
         }
-    }
-    generics
+    };
+    proc_macro::TokenStream::from(expanded)
 }
 
 #[derive(Default, Debug)]
 struct Args {
-    _primary_key: Option<Vec<FieldRef>>,
-    _clustering_keys: Option<Vec<FieldRef>>,
+    partition_keys: Option<Vec<syn::Ident>>,
+    clustering_keys: Option<Vec<syn::Ident>>,
     table_name: Option<String>,
     keyspace: Option<String>,
+    consistency: Option<syn::Ident>,
+    options: TableOptions,
 }
 
-// impl TryFrom<Vec<ExprAssign>> for Args{
-//     type Error = SinInputError;
-
-//     fn try_from(value: Vec<ExprAssign>) -> std::result::Result<Self, Self::Error> {
-//         let map = value.into_iter()
-//             .map(|exp | {
-//                 let left = get_left_path(exp.left)?;
-//                 if left ==
-//                 let right = get_right_info(exp);
-//                 (left, right)
-//             })
-//             .collect::<HashMap<Ident, >>()
-//         Ok(Self::default())
-//     }
-// }
-
-// fn get_left_path(exp : Box<Expr>) -> std::result::Result<Ident, SinInputError>{
-//     match *exp{
-//         Path(path) => {
-//             path.path.get_ident().map(|i| i.clone()).ok_or(())
-//         },
-//         _ => Err(()),
-//     }
-// }
-
-// fn get_array_exp(exp : Box<Expr>) -> std::result::Result<(),SinInputError>{
-//     match *exp{
-//         Expr::Array(arr) =>,
-//         _ => Err(())
-//     }
-// }
-// }
+struct NoSqlModel {
+    table: String,
+    keyspace: String,
+    fields: DbFields,
+    consistency: Option<syn::Ident>,
+    options: TableOptions,
+}
+
+// variants of `Consistency`, serial levels are only set per query with `serial_consistency`
+const CONSISTENCY_LEVELS: [&str; 9] = [
+    "Any",
+    "One",
+    "Two",
+    "Three",
+    "Quorum",
+    "All",
+    "LocalQuorum",
+    "EachQuorum",
+    "LocalOne",
+];
+
+fn duplicate_attribute(key: &syn::Ident) -> syn::Error {
+    syn::Error::new(key.span(), format!("duplicate nosql attribute `{}`", key))
+}
+
+fn set_once<T>(slot: &mut Option<T>, key: &syn::Ident, value: T) -> Result<()> {
+    if slot.is_some() {
+        return Err(duplicate_attribute(key));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn parse_keys(input: ParseStream<'_>) -> Result<Vec<syn::Ident>> {
+    input
+        .parse::<syn::ExprArray>()?
+        .elems
+        .into_iter()
+        .map(|e| match e {
+            syn::Expr::Path(ref path) => path
+                .path
+                .get_ident()
+                .cloned()
+                .ok_or_else(|| syn::Error::new_spanned(&e, "expected a field name")),
+            _ => Err(syn::Error::new_spanned(&e, "expected a field name")),
+        })
+        .collect()
+}
 
 use syn::parse::Parse;
-/// #[read_functions(Table{pkey = (), skey = [(), ()], table_name = name, keyspace = name})]
+/// #[nosql(partition_key = [id], clustering_key = [status], table = name, keyspace = name,
+///     clustering_order = [status = desc], compaction = LeveledCompactionStrategy,
+///     default_ttl = 3600, comment = "payments by id", consistency = LocalQuorum)]
 impl Parse for Args {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let mut _primary_key = None;
-        let mut _clustering_keys = None;
-        let mut table_name = None;
-        let mut keyspace = None;
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut args = Self::default();
 
         while !input.is_empty() {
             let key: syn::Ident = input.parse()?;
@@ -324,34 +666,77 @@ impl Parse for Args {
 
             match key.to_string().as_str() {
                 "table" => {
-                    let value: syn::Expr = input.parse()?;
-                    table_name = Some(value.to_token_stream().to_string());
+                    let value: syn::Ident = input.parse()?;
+                    set_once(&mut args.table_name, &key, value.to_string())?;
                 }
                 "partition_key" => {
-                    let value: Vec<FieldRef> = input
-                        .parse::<syn::ExprArray>()?
-                        .elems
-                        .into_iter()
-                        .map(|e| e.to_token_stream().to_string().into())
-                        .collect();
-
-                    _primary_key = Some(value);
+                    let value = parse_keys(input)?;
+                    if value.is_empty() {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "at least one partition key is required",
+                        ));
+                    }
+                    set_once(&mut args.partition_keys, &key, value)?;
                 }
                 "clustering_key" => {
-                    let value: Vec<FieldRef> = input
-                        .parse::<syn::ExprArray>()?
-                        .elems
-                        .into_iter()
-                        .map(|e| e.to_token_stream().to_string().into())
-                        .collect();
-
-                    _clustering_keys = Some(value);
+                    let value = parse_keys(input)?;
+                    set_once(&mut args.clustering_keys, &key, value)?;
                 }
                 "keyspace" => {
-                    let value: syn::Expr = input.parse()?;
-                    keyspace = Some(value.to_token_stream().to_string());
+                    let value: syn::Ident = input.parse()?;
+                    set_once(&mut args.keyspace, &key, value.to_string())?;
+                }
+                "clustering_order" => {
+                    if !args.options.clustering_order.is_empty() {
+                        return Err(duplicate_attribute(&key));
+                    }
+                    args.options.clustering_order = TableOptions::parse_clustering_order(input)?;
+                }
+                "compaction" => {
+                    let value: syn::Ident = input.parse()?;
+                    set_once(&mut args.options.compaction, &key, value)?;
+                }
+                "default_ttl" => {
+                    let value: syn::LitInt = input.parse()?;
+                    set_once(&mut args.options.default_ttl, &key, value.base10_parse()?)?;
+                }
+                "comment" => {
+                    let value: syn::LitStr = input.parse()?;
+                    set_once(&mut args.options.comment, &key, value.value())?;
+                }
+                "consistency" => {
+                    let value: syn::Ident = input.parse()?;
+                    if value == "Serial" || value == "LocalSerial" {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!(
+                                "`{}` is only valid for conditional writes, set it on the query with `serial_consistency`",
+                                value
+                            ),
+                        ));
+                    }
+                    if !CONSISTENCY_LEVELS.contains(&value.to_string().as_str()) {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            format!(
+                                "unknown consistency `{}`, expected one of {}",
+                                value,
+                                CONSISTENCY_LEVELS.join(", ")
+                            ),
+                        ));
+                    }
+                    set_once(&mut args.consistency, &key, value)?;
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        key.span(),
+                        format!(
+                            "unknown nosql attribute `{}`, expected one of `partition_key`, `clustering_key`, `table`, `keyspace`, `clustering_order`, `compaction`, `default_ttl`, `comment`, `consistency`",
+                            key
+                        ),
+                    ))
                 }
-                _ => {}
             }
 
             if !input.is_empty() {
@@ -359,12 +744,42 @@ impl Parse for Args {
             }
         }
 
-        Ok(Self {
-            _primary_key,
-            _clustering_keys,
-            table_name,
+        Ok(args)
+    }
+}
+
+fn validate_model(args: Args, input: &DeriveInput) -> Result<NoSqlModel> {
+    let mut errors = Errors::default();
+    let missing = |attr: &str| {
+        syn::Error::new(
+            Span::call_site(),
+            format!("missing nosql attribute `{}`", attr),
+        )
+    };
+
+    let table = errors.check(args.table_name.ok_or_else(|| missing("table")));
+    let keyspace = errors.check(args.keyspace.ok_or_else(|| missing("keyspace")));
+    let partition_keys = errors.check(args.partition_keys.ok_or_else(|| missing("partition_key")));
+    let fields = errors.check(get_field_with_types(
+        &input.ident,
+        &input.data,
+        partition_keys.as_deref().unwrap_or_default(),
+        args.clustering_keys.as_deref().unwrap_or_default(),
+    ));
+
+    if let Some(fields) = &fields {
+        args.options.validate(fields, &mut errors);
+    }
+
+    match (table, keyspace, fields) {
+        (Some(table), Some(keyspace), Some(fields)) => errors.finish(NoSqlModel {
+            table,
             keyspace,
-        })
+            fields,
+            consistency: args.consistency,
+            options: args.options,
+        }),
+        _ => Err(errors.into_error()),
     }
 }
 
@@ -375,43 +790,68 @@ pub fn nosql(
 ) -> proc_macro::TokenStream {
     let args: Args = parse_macro_input!(attrs);
     let input: DeriveInput = parse_macro_input!(minput);
-    let table = args.table_name.expect("table name expected");
-    let keyspace = args.keyspace.expect("keyspace expected");
     let name = input.ident.clone();
 
+    let NoSqlModel {
+        table,
+        keyspace,
+        fields,
+        consistency,
+        options,
+    } = match validate_model(args, &input) {
+        Ok(model) => model,
+        Err(error) => return proc_macro::TokenStream::from(error.to_compile_error()),
+    };
+
     let pre_req = quote! {
-        #[derive(sin::ToCqlData, sin::FromCqlData)]
+        #[derive(::sin::ToCqlData, ::sin::FromCqlData, ::sin::SerializeRow, ::sin::DeserializeRow)]
     };
 
+    let (types, client, schema_module) = (types_module(), client_module(), schema_module());
     let query_traits = quote! {
-        impl Selectable for #name{}
-        impl Insertable for #name{}
+        impl #client::Selectable for #name{}
+        impl #client::Insertable for #name{}
+        impl #client::Deletable for #name{}
     };
 
-    let partition_keys = match args._primary_key {
-        Some(k) => k,
-        None => {
-            return proc_macro::TokenStream::from(
-                syn::Error::new(Span::call_site(), "primary keys not found").to_compile_error(),
-            )
-        }
+    let update_struct = generate_update_struct(&name, &input.vis, &fields);
+    let cols = generate_cols(&name, &input.vis, &fields);
+    let keys = generate_keys(&name, &input.vis, &fields);
+    let insert_statement = generate_insert(&table, &keyspace, &fields.fields);
+    let create_table_statement =
+        match ddl::generate_create_table(&table, &keyspace, &fields, &options) {
+            Ok(statement) => quote! { Ok(#statement) },
+            Err(column) => quote! {
+                Err(#schema_module::UnknownCqlType { column: #column })
+            },
+        };
+    let schema = ddl::generate_schema(&table, &keyspace, &fields, &options);
+    let default_ttl = match options.default_ttl {
+        Some(ttl) => quote! { Some(std::time::Duration::from_secs(u64::from(#ttl))) },
+        None => quote! { None },
     };
-    let clustering_keys = args._clustering_keys;
-
-    let fields = match get_field_with_types(&input.data, partition_keys, clustering_keys) {
-        Some(a) => a,
-        None => {
-            return proc_macro::TokenStream::from(
-                syn::Error::new(Span::call_site(), "expected struct with named fields")
-                    .to_compile_error(),
-            )
+    // the trait default is used when the model doesn't set one
+    let consistency = consistency.map(|consistency| {
+        quote! {
+            fn consistency() -> Option<#types::Consistency>{
+                Some(#types::Consistency::#consistency)
+            }
         }
-    };
+    });
 
-    let insert_statement = generate_insert(&table, &keyspace, fields.fields);
+    let (key_name, primary_key_name) = key_names(&name);
+    let key_fields = fields.partition_keys.iter().map(|f| &f.ident);
+    let primary_key_fields = fields
+        .partition_keys
+        .iter()
+        .chain(fields.clustering_keys.iter())
+        .map(|f| &f.ident);
 
     let nosql = quote! {
-        impl NoSql for #name {
+        impl ::traits::nosql::interface::NoSql for #name {
+            type Key = #key_name;
+            type PrimaryKey = #primary_key_name;
+
             fn table_name() -> &'static str{
                 #table
             }
@@ -423,11 +863,37 @@ pub fn nosql(
             fn insert_statement() -> &'static str{
                 #insert_statement
             }
+
+            fn create_table_statement() -> Result<&'static str, #schema_module::UnknownCqlType>{
+                #create_table_statement
+            }
+
+            fn default_ttl() -> Option<std::time::Duration>{
+                #default_ttl
+            }
+
+            #consistency
+
+            fn schema() -> &'static #schema_module::TableSchema{
+                #schema
+            }
+
+            fn key(&self) -> #key_name {
+                #key_name {
+                    #(#key_fields : self.#key_fields.clone()),*
+                }
+            }
+
+            fn primary_key(&self) -> #primary_key_name {
+                #primary_key_name {
+                    #(#primary_key_fields : self.#primary_key_fields.clone()),*
+                }
+            }
         }
 
     };
 
-    let filters = generate_filters(fields.partition_keys, fields.clustering_keys);
+    let filters = generate_filters(&input.vis, &table, &keyspace, &fields);
 
     let gen_filters = {
         quote! {
@@ -443,20 +909,290 @@ pub fn nosql(
         #nosql
         #query_traits
         #gen_filters
+        #update_struct
+        #cols
+        #keys
     })
 }
 
-fn generate_insert(table: &str, keyspace: &str, fields: Vec<Rc<NoSqlField>>) -> String {
+fn is_key(fields: &DbFields, field: &NoSqlField) -> bool {
+    fields
+        .partition_keys
+        .iter()
+        .chain(fields.clustering_keys.iter())
+        .any(|key| field.ident == key.ident)
+}
+
+// generates `{Model}Update` with an optional field for every non key column,
+// primary key columns can't be part of a SET clause
+fn generate_update_struct(
+    name: &syn::Ident,
+    vis: &syn::Visibility,
+    fields: &DbFields,
+) -> TokenStream {
+    let columns: Vec<&NoSqlField> = fields
+        .fields
+        .iter()
+        .map(|f| f.as_ref())
+        .filter(|f| !is_key(fields, f))
+        .collect();
+
+    if columns.is_empty() {
+        return TokenStream::new();
+    }
+
+    let update_name = quote::format_ident!("{}Update", name);
+    let capacity = columns.len();
+
+    let struct_fields = columns.iter().map(|f| {
+        let (ident, ty, field_vis) = (&f.ident, &f.ty, &f.vis);
+        quote! {
+            #field_vis #ident : Option<#ty>
+        }
+    });
+
+    let (types, client) = (types_module(), client_module());
+    let set_columns = columns.iter().map(|f| {
+        let ident = &f.ident;
+        let set_clause = format!("{} = ?", f.column);
+        quote! {
+            if self.#ident.is_some() {
+                set_clause.push(#set_clause);
+            }
+        }
+    });
+    let set_binds = columns.iter().map(|f| {
+        let (ident, column) = (&f.ident, &f.column);
+        quote! {
+            if let Some(value) = self.#ident {
+                binds.insert(#column.to_string(), #types::ToCqlData::to_cql(value));
+            }
+        }
+    });
+
+    // fields that are `None` are bound as unset, for statements with every column in SET
+    let row = row_module();
+    let serialize = serialize_row(
+        &update_name,
+        columns
+            .iter()
+            .map(|f| {
+                let ident = &f.ident;
+                let value = quote! {
+                    match &self.#ident {
+                        Some(value) => #row::SerializeValue::serialize(value, typ, writer.make_cell_writer()),
+                        None => Ok(writer.make_cell_writer().set_unset()),
+                    }
+                };
+                (f.column.clone(), value)
+            })
+            .collect(),
+    );
+
+    quote! {
+        #[derive(Default)]
+        #vis struct #update_name {
+            #(#struct_fields),*
+        }
+
+        #serialize
+
+        impl #client::Updateable for #update_name {
+            type ParentModel = #name;
+
+            fn set_clause(&self) -> String {
+                let mut set_clause: Vec<&'static str> = Vec::with_capacity(#capacity);
+                #(#set_columns)*
+                set_clause.join(", ")
+            }
+        }
+
+        impl #types::ToCqlRow for #update_name {
+            type Output = #types::CqlMapWithQuery;
+
+            fn to_row_iter(self) -> impl Iterator<Item = Self::Output> {
+                let set_clause = #client::Updateable::set_clause(&self);
+                let mut binds: #types::CqlMap = ::std::collections::HashMap::with_capacity(#capacity);
+                #(#set_binds)*
+                ::std::iter::once((set_clause.into(), binds))
+            }
+        }
+    }
+}
+
+// `col = ?` for every non key column, `None` if the model has no such column
+fn update_set_clause(fields: &DbFields) -> Option<String> {
+    let set_clause = fields
+        .fields
+        .iter()
+        .filter(|f| !is_key(fields, f))
+        .map(|f| format!("{} = ?", f.column))
+        .collect::<Vec<String>>();
+    (!set_clause.is_empty()).then(|| set_clause.join(", "))
+}
+
+// `SELECT_BY_{KEYS}`, `DELETE_BY_{KEYS}` and `UPDATE_BY_{KEYS}` constants of a key prefix,
+// updates need the whole primary key so `UPDATE_BY_{KEYS}` only exists for it
+fn statement_names(keys: &[&syn::Ident]) -> [syn::Ident; 3] {
+    let keys = keys
+        .iter()
+        .map(|key| key.to_string().to_uppercase())
+        .collect::<Vec<String>>()
+        .join("_");
+    ["SELECT", "DELETE", "UPDATE"]
+        .map(|statement| quote::format_ident!("{}_BY_{}", statement, keys))
+}
+
+// attaches the statement constants of a key prefix to a `FilterBy` of `model`,
+// `update_set` is only given for the whole primary key
+fn with_statements(
+    model: &TokenStream,
+    keys: &[&syn::Ident],
+    update_set: Option<&str>,
+) -> TokenStream {
+    let [select, delete, update] = statement_names(keys);
+    let update = update_set.map(|set_clause| {
+        quote! { .with_update_statement(#set_clause, #model::#update) }
+    });
+    quote! {
+        .with_statements(#model::#select, #model::#delete)
+        #update
+    }
+}
+
+// generates `{Model}Cols` with a typed column handle for every column, used
+// to build where clauses through `Model::cols()`
+fn generate_cols(name: &syn::Ident, vis: &syn::Visibility, fields: &DbFields) -> TokenStream {
+    let cols_name = quote::format_ident!("{}Cols", name);
+    let column = column_module();
+
+    let kind_of = |f: &NoSqlField| {
+        if fields.partition_keys.iter().any(|key| key.ident == f.ident) {
+            quote! { #column::Partition }
+        } else if fields.clustering_keys.iter().any(|key| key.ident == f.ident) {
+            quote! { #column::Clustering }
+        } else {
+            quote! { #column::Regular }
+        }
+    };
+
+    let struct_fields = fields.fields.iter().map(|f| {
+        let (ident, ty, field_vis) = (&f.ident, &f.ty, &f.vis);
+        let kind = kind_of(f);
+        quote! {
+            #field_vis #ident : #column::Col<#name, #ty, #kind>
+        }
+    });
+
+    let init_fields = fields.fields.iter().map(|f| {
+        let (ident, column_name) = (&f.ident, &f.column);
+        quote! {
+            #ident : #column::Col::new(#column_name)
+        }
+    });
+
+    quote! {
+        #[derive(Clone, Copy)]
+        #vis struct #cols_name {
+            #(#struct_fields),*
+        }
+
+        impl #name {
+            #vis const fn cols() -> #cols_name {
+                #cols_name {
+                    #(#init_fields),*
+                }
+            }
+        }
+    }
+}
+
+fn key_names(name: &syn::Ident) -> (syn::Ident, syn::Ident) {
+    (
+        quote::format_ident!("{}Key", name),
+        quote::format_ident!("{}PrimaryKey", name),
+    )
+}
+
+// generates `{Model}Key` with the partition key columns and `{Model}PrimaryKey` with
+// the whole primary key, both convert into an equality filter on their columns
+fn generate_keys(name: &syn::Ident, vis: &syn::Visibility, fields: &DbFields) -> TokenStream {
+    let (key_name, primary_key_name) = key_names(name);
+    let update_set = update_set_clause(fields);
+    let (types, client) = (types_module(), client_module());
+
+    let key_struct = |struct_name: &syn::Ident, keys: &[&NoSqlField], update_set: Option<&str>| {
+        let struct_fields = keys.iter().map(|f| {
+            let (ident, ty, field_vis) = (&f.ident, &f.ty, &f.vis);
+            // decoded from rows keyed by column name
+            let rename = (*ident != f.column).then(|| {
+                let column = &f.column;
+                quote! { #[sin(rename = #column)] }
+            });
+            quote! {
+                #rename
+                #field_vis #ident : #ty
+            }
+        });
+        let binds = keys.iter().map(|f| {
+            let (ident, column) = (&f.ident, &f.column);
+            quote! {
+                (#column.to_string(), #types::ToCqlData::to_cql(key.#ident))
+            }
+        });
+        let relation = relation();
+        let columns = keys.iter().map(|f| &f.column);
+        let key_idents = keys.iter().map(|f| &f.ident).collect::<Vec<&syn::Ident>>();
+        let statements = with_statements(&quote! { #name }, &key_idents, update_set);
+
+        quote! {
+            #[derive(Debug, Clone, ::sin::FromCqlData, ::sin::SerializeRow, ::sin::DeserializeRow)]
+            #vis struct #struct_name {
+                #(#struct_fields),*
+            }
+
+            impl From<#struct_name> for #client::FilterBy<#name> {
+                fn from(key: #struct_name) -> Self {
+                    const RELATIONS: &[#relation] = &[#(#relation::eq(#columns)),*];
+                    #client::FilterBy::<#name>::new(
+                        ::std::collections::HashMap::from([#(#binds),*]),
+                        RELATIONS,
+                    )
+                        #statements
+                }
+            }
+        }
+    };
+
+    let partition_keys: Vec<&NoSqlField> =
+        fields.partition_keys.iter().map(|f| f.as_ref()).collect();
+    let primary_keys: Vec<&NoSqlField> = fields
+        .partition_keys
+        .iter()
+        .chain(fields.clustering_keys.iter())
+        .map(|f| f.as_ref())
+        .collect();
+
+    // the partition key is the whole primary key of tables without clustering keys
+    let key_update_set = update_set
+        .as_deref()
+        .filter(|_| fields.clustering_keys.is_empty());
+    let key = key_struct(&key_name, &partition_keys, key_update_set);
+    let primary_key = key_struct(&primary_key_name, &primary_keys, update_set.as_deref());
+    quote! {
+        #key
+        #primary_key
+    }
+}
+
+fn generate_insert(table: &str, keyspace: &str, fields: &[Rc<NoSqlField>]) -> String {
     let col_len = fields.len();
     let col: String = fields
-        .into_iter()
-        .map(|f: Rc<NoSqlField>| f.ident.to_string())
+        .iter()
+        .map(|f| f.column.clone())
         .collect::<Vec<String>>()
         .join(",");
-    let binds = std::iter::repeat("?")
-        .take(col_len)
-        .collect::<Vec<&str>>()
-        .join(",");
+    let binds = vec!["?"; col_len].join(",");
     format!(
         "INSERT INTO {}.{} ({}) VALUES({})",
         keyspace, table, col, binds
@@ -464,97 +1200,249 @@ fn generate_insert(table: &str, keyspace: &str, fields: Vec<Rc<NoSqlField>>) ->
 }
 
 struct FilterByBuilder {
-    data_map: Vec<(syn::Ident, syn::Type)>,
+    data_map: Vec<(syn::Ident, syn::Type, String)>,
     query_string: String,
     fn_prefix: String,
+    vis: syn::Visibility,
+    // `keyspace.table` of the generated statements
+    table: String,
+    update_set: Option<String>,
+    primary_key_len: usize,
 }
 
 impl FilterByBuilder {
     //TODO: add approx for string size as well
-    fn new(field_size: usize) -> Self {
+    fn new(
+        field_size: usize,
+        vis: &syn::Visibility,
+        table: String,
+        update_set: Option<String>,
+    ) -> Self {
         Self {
             data_map: Vec::with_capacity(field_size),
             query_string: String::new(),
             fn_prefix: String::from("filter_by"),
+            vis: vis.clone(),
+            table,
+            update_set,
+            primary_key_len: field_size,
         }
     }
 
-    fn add(&mut self, name: &String, ident: &syn::Ident, ty: &syn::Type) {
-        if self.query_string.len() == 0 {
-            self.query_string.extend([name.as_str(), " = ?"]);
+    fn keys(&self) -> Vec<&syn::Ident> {
+        self.data_map.iter().map(|(ident, _, _)| ident).collect()
+    }
+
+    // SET clause of `UPDATE_BY_{KEYS}`, only once the prefix is the whole primary key
+    fn update_set(&self) -> Option<&str> {
+        self.update_set
+            .as_deref()
+            .filter(|_| self.data_map.len() == self.primary_key_len)
+    }
+
+    // statements of the current key prefix as constants of the model
+    fn statement_tokens(&self) -> TokenStream {
+        let vis = &self.vis;
+        let keys = self.keys();
+        let [select_name, delete_name, update_name] = statement_names(&keys);
+
+        let select = format!("SELECT * FROM {} WHERE {}", self.table, self.query_string);
+        let delete = format!("DELETE FROM {} WHERE {}", self.table, self.query_string);
+        let update = self.update_set().map(|set_clause| {
+            let update = format!(
+                "UPDATE {} SET {} WHERE {}",
+                self.table, set_clause, self.query_string
+            );
+            quote! {
+                #vis const #update_name: &'static str = #update;
+            }
+        });
+        quote! {
+            #vis const #select_name: &'static str = #select;
+            #vis const #delete_name: &'static str = #delete;
+            #update
+        }
+    }
+
+    // `{prefix} AND {column} >= ? AND {column} < ?` for the current key prefix, a range is
+    // only allowed on the last restricted clustering key so it is generated per clustering key
+    fn range_tokens(&self, field: &NoSqlField) -> TokenStream {
+        let vis = &self.vis;
+        let (ident, ty, column) = (&field.ident, &field.ty, &field.column);
+        let fn_name = quote::format_ident!("{}_{}_range", self.fn_prefix, ident);
+        let (types, client) = (types_module(), client_module());
+
+        let fn_sig = self.data_map.iter().map(|(ident, ty, _)| {
+            quote! {
+                #ident : #ty
+            }
+        });
+        let fn_body = self.data_map.iter().map(|(ident, _, column)| {
+            quote! {
+                (#column.to_string(), #types::ToCqlData::to_cql(#ident))
+            }
+        });
+
+        // named markers, both bounds restrict the same column
+        let (lower, upper) = (format!("{}_lower", column), format!("{}_upper", column));
+        let (relation, relations) = (relation(), self.relations());
+
+        quote! {
+            #vis fn #fn_name<V>(#(#fn_sig,)* #ident : impl ::std::ops::RangeBounds<V>) -> #client::FilterBy<Self>
+            where
+                V: Clone + Into<#ty>,
+            {
+                use ::std::ops::Bound;
+
+                let mut filter = ::std::collections::HashMap::from([#(#fn_body),*]);
+                let mut relations = vec![#(#relations),*];
+                let lower = match #ident.start_bound() {
+                    Bound::Included(value) => Some((">=", value)),
+                    Bound::Excluded(value) => Some((">", value)),
+                    Bound::Unbounded => None,
+                };
+                if let Some((operator, value)) = lower {
+                    filter.insert(#lower.to_string(), #types::ToCqlData::to_cql(Into::<#ty>::into(value.clone())));
+                    relations.push(#relation::named(#column, operator, #lower));
+                }
+                let upper = match #ident.end_bound() {
+                    Bound::Included(value) => Some(("<=", value)),
+                    Bound::Excluded(value) => Some(("<", value)),
+                    Bound::Unbounded => None,
+                };
+                if let Some((operator, value)) = upper {
+                    filter.insert(#upper.to_string(), #types::ToCqlData::to_cql(Into::<#ty>::into(value.clone())));
+                    relations.push(#relation::named(#column, operator, #upper));
+                }
+
+                #client::FilterBy::<Self>::new(filter, relations)
+            }
+        }
+    }
+
+    // `{prefix} AND {column} IN ?` with the values bound as one list, generated for the
+    // last partition key and for every clustering key
+    fn in_tokens(&self, field: &NoSqlField) -> TokenStream {
+        let vis = &self.vis;
+        let (ident, ty, column) = (&field.ident, &field.ty, &field.column);
+        let fn_name = quote::format_ident!("{}_{}_in", self.fn_prefix, ident);
+        let (types, client) = (types_module(), client_module());
+
+        let fn_sig = self.data_map.iter().map(|(ident, ty, _)| {
+            quote! {
+                #ident : #ty
+            }
+        });
+        let fn_body = self.data_map.iter().map(|(ident, _, column)| {
+            quote! {
+                (#column.to_string(), #types::ToCqlData::to_cql(#ident))
+            }
+        });
+
+        let bind_name = format!("{}_in", column);
+        let (relation, relations) = (relation(), self.relations());
+
+        quote! {
+            #vis fn #fn_name(#(#fn_sig,)* #ident : Vec<#ty>) -> #client::FilterBy<Self> {
+                let filter = ::std::collections::HashMap::from([#(#fn_body,)* (#bind_name.to_string(), #types::ToCqlData::to_cql(#ident))]);
+                let relations = vec![#(#relations,)* #relation::named(#column, "IN", #bind_name)];
+                #client::FilterBy::<Self>::new(filter, relations)
+            }
+        }
+    }
+
+    // equality on every column of the current key prefix
+    fn relations(&self) -> Vec<TokenStream> {
+        let relation = relation();
+        self.data_map
+            .iter()
+            .map(|(_, _, column)| quote! { #relation::eq(#column) })
+            .collect()
+    }
+
+    fn add(&mut self, field: &NoSqlField) {
+        if self.query_string.is_empty() {
+            self.query_string.extend([field.column.as_str(), " = ?"]);
         } else {
-            self.query_string.extend([" AND ", name.as_str(), " = ?"]);
+            self.query_string
+                .extend([" AND ", field.column.as_str(), " = ?"]);
         }
-        self.fn_prefix.extend(["_", name.as_str()]);
+        self.fn_prefix
+            .extend(["_".to_string(), field.ident.to_string()]);
 
-        self.data_map.push((ident.clone(), ty.clone()));
+        self.data_map
+            .push((field.ident.clone(), field.ty.clone(), field.column.clone()));
     }
 }
 
 impl ToTokens for FilterByBuilder {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let fn_sig = self.data_map.iter().map(|(ident, ty)| {
+        let (types, client) = (types_module(), client_module());
+        let fn_sig = self.data_map.iter().map(|(ident, ty, _)| {
             quote! {
                 #ident : #ty
             }
         });
 
-        let fn_body = self.data_map.iter().map(|(ident, _)| {
+        let fn_body = self.data_map.iter().map(|(ident, _, column)| {
             quote! {
-                (stringify!(#ident).to_string(), #ident.to_cql())
+                (#column.to_string(), #types::ToCqlData::to_cql(#ident))
             }
         });
-        let fn_name = syn::Ident::new(&self.fn_prefix.as_str(), Span::call_site().into());
-
-        let query_string = syn::Lit::Str(syn::LitStr::new(
-            self.query_string.as_str(),
-            Span::call_site().into(),
-        ));
+        let fn_name = syn::Ident::new(self.fn_prefix.as_str(), Span::call_site());
+
+        let relations = self.relations();
+        let vis = &self.vis;
+        let constants = self.statement_tokens();
+        let keys = self.keys();
+        let statements = with_statements(&quote! { Self }, &keys, self.update_set());
+        let relation = relation();
         let res = quote! {
-            fn #fn_name (#(#fn_sig),*) -> FilterBy<Self>{
-                let filter = HashMap::from([#(#fn_body),*]);
-                FilterBy::<Self>::new(filter, #query_string)
+            #constants
+
+            #vis fn #fn_name (#(#fn_sig),*) -> #client::FilterBy<Self>{
+                const RELATIONS: &[#relation] = &[#(#relations),*];
+                let filter = ::std::collections::HashMap::from([#(#fn_body),*]);
+                #client::FilterBy::<Self>::new(filter, RELATIONS)
+                    #statements
             }
         };
         tokens.extend(res);
     }
 }
 
-fn len_option<T>(v: &Option<Vec<T>>) -> usize {
-    match v {
-        None => 0,
-        Some(ve) => ve.len(),
-    }
-}
-
+// `filter_by_{keys}` filters for every key prefix with the select and delete
+// statements using them, and the update statement of the whole primary key
 fn generate_filters(
-    partition_keys: Vec<FieldRef>,
-    clustering_keys: Option<Vec<FieldRef>>,
+    vis: &syn::Visibility,
+    table: &str,
+    keyspace: &str,
+    fields: &DbFields,
 ) -> TokenStream {
-    let field_size = partition_keys.len() + len_option(&clustering_keys);
+    let (partition_keys, clustering_keys) = (&fields.partition_keys, &fields.clustering_keys);
+    let field_size = partition_keys.len() + clustering_keys.len();
     let mut token_stream = TokenStream::new();
-    let mut filter_builder = FilterByBuilder::new(field_size);
-
-    for i in partition_keys.iter() {
-        filter_builder.add(
-            &i.name,
-            &i.index.as_deref().unwrap().ident,
-            &i.index.as_deref().unwrap().ty,
-        );
+    let mut filter_builder = FilterByBuilder::new(
+        field_size,
+        vis,
+        format!("{}.{}", keyspace, table),
+        update_set_clause(fields),
+    );
+
+    for (index, i) in partition_keys.iter().enumerate() {
+        if index + 1 == partition_keys.len() {
+            token_stream.extend(filter_builder.in_tokens(i));
+        }
+        filter_builder.add(i);
     }
     filter_builder.to_tokens(&mut token_stream);
 
-    if let Some(cluster_keys) = clustering_keys {
-        for i in cluster_keys.iter() {
-            filter_builder.add(
-                &i.name,
-                &i.index.as_deref().unwrap().ident,
-                &i.index.as_deref().unwrap().ty,
-            );
-            filter_builder.to_tokens(&mut token_stream);
-        }
+    for i in clustering_keys.iter() {
+        token_stream.extend(filter_builder.in_tokens(i));
+        token_stream.extend(filter_builder.range_tokens(i));
+        filter_builder.add(i);
+        filter_builder.to_tokens(&mut token_stream);
     }
 
     token_stream
 }
-
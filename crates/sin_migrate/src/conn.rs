@@ -1,7 +1,9 @@
-use scylla::{Session, SessionBuilder};
+use crate::error;
+use scylla::SessionBuilder;
+use traits::clients::scylla_cql::session::ScyllaSession;
 
 pub(crate) struct Conn {
-    pub(crate) conn: Session,
+    pub(crate) conn: ScyllaSession,
 }
 
 impl Conn {
@@ -10,23 +12,24 @@ impl Conn {
         username: Option<String>,
         password: Option<String>,
         keyspace: String,
-    ) -> Self {
+    ) -> error::CustomResult<Self> {
         let mut session = SessionBuilder::new()
             .known_node(node)
             .use_keyspace(keyspace, false);
 
         if let Some(username) = username {
-            session = session.user(username, password.expect("Password shouldn't be empty"));
+            let password = password.ok_or(error::Error::ArgumentError("DATBASE_PASSWORD"))?;
+            session = session.user(username, password);
         }
 
-        Self {
-            conn: session.build().await.expect("Unable to reach the database"),
-        }
+        Ok(Self {
+            conn: session.build().await?.into(),
+        })
     }
 }
 
 impl std::ops::Deref for Conn {
-    type Target = Session;
+    type Target = ScyllaSession;
 
     fn deref(&self) -> &Self::Target {
         &self.conn
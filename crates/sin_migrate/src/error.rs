@@ -1,4 +1,7 @@
-use scylla::transport::errors::QueryError;
+use scylla::{
+    deserialize::TypeCheckError,
+    transport::errors::{NewSessionError, QueryError},
+};
 use thiserror::Error;
 
 pub(crate) type CustomResult<T> = Result<T, Error>;
@@ -13,4 +16,12 @@ pub(crate) enum Error {
     MigrationPathError,
     #[error("Queryerror from Sin {0:?}")]
     SinQueryErrir(#[from] traits::query::query::QueryError),
+    #[error("Unable to reach the database {0:?}")]
+    ConnectionError(#[from] NewSessionError),
+    #[error("Migration metadata doesn't match the schema table {0:?}")]
+    MetadataError(#[from] TypeCheckError),
+    #[error("Missing argument {0}")]
+    ArgumentError(&'static str),
+    #[error("Missing command, use `migrate generate|run|redo|undo`")]
+    CommandError,
 }
@@ -1,3 +1,6 @@
+// the cli reports progress and errors on the terminal
+#![allow(clippy::print_stdout, clippy::print_stderr)]
+
 use colored::Colorize;
 
 mod cli;
@@ -13,9 +16,9 @@ async fn inner_main() -> error::CustomResult<()> {
     let args = cli::Cli::build();
     let matches = args.command().get_matches();
 
-    match matches.subcommand().expect("No args") {
-        ("migrate", matches) => migrations::run_migration(matches).await,
-        _ => panic!("No args"),
+    match matches.subcommand() {
+        Some(("migrate", matches)) => migrations::run_migration(matches).await,
+        _ => Err(error::Error::CommandError),
     }
 }
 
@@ -24,6 +27,6 @@ async fn main() {
     if let Err(err) = inner_main().await {
         let err = err.to_string().red();
 
-        eprintln!("{}", err)
+        eprintln!("{}", err);
     }
 }
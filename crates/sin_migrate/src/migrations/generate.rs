@@ -1,10 +1,8 @@
-use crate::{consts, error};
+use crate::{consts, error, utils::required_arg};
 use std::{io::Write, path::PathBuf};
 
 pub fn generate_migration_file(args: &clap::ArgMatches) -> error::CustomResult<()> {
-    let name = args
-        .get_one::<String>("GENERATE_NAME")
-        .expect("This should not be null should be prevented by clap");
+    let name = required_arg(args, "GENERATE_NAME")?;
 
     let dir = args
         .get_one::<PathBuf>("MIGRATION_DIR")
@@ -3,24 +3,17 @@ pub(crate) mod redo;
 pub(crate) mod run;
 pub(crate) mod undo;
 
-use crate::{conn::Conn, error};
+use crate::{conn::Conn, error, utils::required_arg};
 use std::path::PathBuf;
 
 pub async fn run_migration(args: &clap::ArgMatches) -> error::CustomResult<()> {
-    match args.subcommand().expect("Invalid") {
-        ("generate", args) => generate::generate_migration_file(args),
-        ("run", args) => {
-            let url = args
-                .get_one::<String>("DATABASE_URL")
-                .cloned()
-                .expect("This should be prevented from the CLI");
-
-            let keyspace = args
-                .get_one::<String>("KEYSPACE")
-                .cloned()
-                .expect("This should be prevented from CLI");
+    match args.subcommand() {
+        Some(("generate", args)) => generate::generate_migration_file(args),
+        Some(("run", args)) => {
+            let url = required_arg(args, "DATABASE_URL")?;
+            let keyspace = required_arg(args, "KEYSPACE")?;
 
-            let conn = Conn::from_url(url, None, None, keyspace).await;
+            let conn = Conn::from_url(url, None, None, keyspace).await?;
 
             let dir = args
                 .get_one::<PathBuf>("MIGRATION_DIR")
@@ -29,18 +22,11 @@ pub async fn run_migration(args: &clap::ArgMatches) -> error::CustomResult<()> {
 
             run::run_migrations(dir, &conn).await
         }
-        ("redo", args) => {
-            let url = args
-                .get_one::<String>("DATABASE_URL")
-                .cloned()
-                .expect("This should be prevented from the CLI");
+        Some(("redo", args)) => {
+            let url = required_arg(args, "DATABASE_URL")?;
+            let keyspace = required_arg(args, "KEYSPACE")?;
 
-            let keyspace = args
-                .get_one::<String>("KEYSPACE")
-                .cloned()
-                .expect("This should be prevented from CLI");
-
-            let conn = Conn::from_url(url, None, None, keyspace).await;
+            let conn = Conn::from_url(url, None, None, keyspace).await?;
 
             let dir = args
                 .get_one::<PathBuf>("MIGRATION_DIR")
@@ -49,18 +35,11 @@ pub async fn run_migration(args: &clap::ArgMatches) -> error::CustomResult<()> {
 
             redo::redo_migrations(dir, conn).await
         }
-        ("undo", args) => {
-            let url = args
-                .get_one::<String>("DATABASE_URL")
-                .cloned()
-                .expect("This should be prevented from the CLI");
-
-            let keyspace = args
-                .get_one::<String>("KEYSPACE")
-                .cloned()
-                .expect("This should be prevented from CLI");
+        Some(("undo", args)) => {
+            let url = required_arg(args, "DATABASE_URL")?;
+            let keyspace = required_arg(args, "KEYSPACE")?;
 
-            let conn = Conn::from_url(url, None, None, keyspace).await;
+            let conn = Conn::from_url(url, None, None, keyspace).await?;
 
             let dir = args
                 .get_one::<PathBuf>("MIGRATION_DIR")
@@ -69,6 +48,6 @@ pub async fn run_migration(args: &clap::ArgMatches) -> error::CustomResult<()> {
 
             undo::undo_migrations(dir, conn).await
         }
-        _ => panic!("invalid"),
+        _ => Err(error::Error::CommandError),
     }
 }
@@ -5,14 +5,9 @@ use crate::{
     utils,
 };
 use colored::Colorize;
-use futures::{StreamExt, TryStreamExt};
-use scylla::Session;
+use futures::StreamExt;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
-use std::{
-    collections::{BTreeMap, HashMap},
-    ops::Deref,
-};
-use traits::query::{client::Insertable, query::QueryInterface};
 
 pub(crate) struct MigrationsToRun {
     tree: BTreeMap<String, PathBuf>,
@@ -53,35 +48,24 @@ impl MigrationsToRun {
 
         let query = "SELECT * from metadata.migration_metadata";
 
-        let mut results = conn
-            .query_iter(query, &[])
-            .await?
-            .rows_stream::<Schema>()
-            .unwrap();
+        let results = conn.query_iter(query, &[]).await?.rows_stream::<Schema>()?;
 
         let results = results
-            .filter_map(|f| async {
-                if let Ok(v) = f {
-                    Some(v)
-                } else {
-                    None
-                }
-            })
+            .filter_map(|f| async move { f.ok() })
             .collect::<Vec<Schema>>()
             .await;
 
         let migrations_not_run = results.iter().filter(|s| !s.is_run).map(|s| &s.version);
 
-        let fresh_migrations = dirs
-            .into_iter()
-            .filter(|version| !results.iter().any(|s| s.version.eq(*version)));
+        let fresh_migrations =
+            dirs.filter(|version| !results.iter().any(|s| s.version.eq(*version)));
 
         let mut results = fresh_migrations
             .chain(migrations_not_run)
-            .map(|s| s.clone())
+            .cloned()
             .collect::<Vec<String>>();
 
-        results.sort_unstable_by(|a, b| a.cmp(b));
+        results.sort_unstable();
         Ok(results)
     }
 }
@@ -4,8 +4,7 @@ pub use schema::*;
 
 use colored::Colorize;
 
-use crate::{conn::Conn, consts, error, migrations::run::run_migrations};
-use std::path::PathBuf;
+use crate::{conn::Conn, consts, error};
 
 pub async fn setup_migration(conn: &Conn) -> error::CustomResult<()> {
     let announce = "Setting up migrations..".green();
@@ -21,6 +20,7 @@ pub async fn setup_migration(conn: &Conn) -> error::CustomResult<()> {
     for stmt in stmts {
         conn.query_unpaged(stmt, &[]).await?;
     }
+    conn.await_schema_agreement().await?;
 
     Ok(())
 }
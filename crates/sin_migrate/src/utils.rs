@@ -1,14 +1,14 @@
 use crate::{conn::Conn, consts, error, setup::Schema};
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use traits::query::{
     client::{Insertable, Selectable},
     query::QueryInterface,
 };
 
-pub fn parse_cql_statements(file: &PathBuf) -> error::CustomResult<Vec<String>> {
-    Ok(std::fs::read_to_string(&file)?
+pub fn parse_cql_statements(file: &Path) -> error::CustomResult<Vec<String>> {
+    Ok(std::fs::read_to_string(file)?
         .replace(consts::PLACEHOLDER, "")
         .replace("\n", "")
         .split_inclusive(";")
@@ -28,7 +28,7 @@ pub async fn is_fresh_migration(conn: &Conn) -> bool {
 pub fn get_migration_tree(dir: std::fs::ReadDir) -> error::CustomResult<BTreeMap<String, PathBuf>> {
     let mut tree = BTreeMap::<String, PathBuf>::new();
 
-    for dir in dir.into_iter() {
+    for dir in dir {
         let path = dir?.path();
         let version = extract_version(&path)?;
         tree.insert(version, path);
@@ -38,7 +38,7 @@ pub fn get_migration_tree(dir: std::fs::ReadDir) -> error::CustomResult<BTreeMap
 }
 
 pub async fn run_cql_queries(
-    file: &PathBuf,
+    file: &Path,
     conn: &Conn,
     version: &str,
     is_run: bool,
@@ -48,20 +48,28 @@ pub async fn run_cql_queries(
     for statement in cql_statements {
         conn.query_unpaged(statement, &[]).await?;
     }
+    // statements prepared before the migration are prepared again
+    conn.await_schema_agreement().await?;
     insert_metadata(conn, version, is_run).await?;
     Ok(())
 }
 
-pub fn extract_version(path: &PathBuf) -> error::CustomResult<String> {
+pub fn extract_version(path: &Path) -> error::CustomResult<String> {
     path.file_name()
-        .map(|st| {
+        .and_then(|st| {
             let s = st.to_string_lossy().to_string();
             s.split_once("_").map(|s| s.0.to_string())
         })
-        .flatten()
         .ok_or(error::Error::MigrationPathError)
 }
 
+// arguments clap already requires are still read fallibly
+pub fn required_arg(args: &clap::ArgMatches, id: &'static str) -> error::CustomResult<String> {
+    args.get_one::<String>(id)
+        .cloned()
+        .ok_or(error::Error::ArgumentError(id))
+}
+
 async fn insert_metadata(conn: &Conn, version: &str, is_run: bool) -> error::CustomResult<()> {
     let now = time::OffsetDateTime::now_utc();
     let schema = Schema::new(version.to_string(), now, is_run);
@@ -1,44 +1,192 @@
 use crate::{
-    data_types::types::{CqlType, IntoValue, ToCqlRow},
+    data_types::types::{
+        Consistency, CqlMap, CqlMapWithQuery, CqlType, FromCqlData, IntoValue, QueryString,
+        SerialConsistency, ToCqlRow,
+    },
     nosql::interface::{CqlStore, NoSql},
-    query::query::{Create, FindOne, QueryError, QueryInterface},
+    query::{
+        execution::{Attempts, Execution},
+        page::{driver_page_size, Cursor, FindPage, Page},
+        query::{
+            Aggregate, Batch, BatchType, Create, Delete, DistinctKeys, FindAll, FindOne, Lwt,
+            LwtResult, QueryError, QueryInterface, QueryStream, Update,
+        },
+    },
 };
-use stargate_grpc::{query::QueryBuilder, Query};
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use stargate_grpc::{Query, Value};
+use std::num::NonZeroU32;
+
+pub enum AstrRequest {
+    Query(AstrStatement),
+    Batch(Batch),
+}
+
+impl AstrRequest {
+    fn execution(&self) -> (Execution, Option<Consistency>) {
+        match self {
+            Self::Query(statement) => (statement.execution.clone(), statement.consistency),
+            Self::Batch(batch) => (batch.execution.clone(), batch.consistency),
+        }
+    }
+}
+
+impl From<AstrStatement> for AstrRequest {
+    fn from(statement: AstrStatement) -> Self {
+        Self::Query(statement)
+    }
+}
+
+#[derive(Clone)]
+pub enum AstrQuery {
+    Query(Query),
+    Batch(stargate_grpc::Batch),
+}
+
+impl AstrQuery {
+    // retries can run with a lower consistency than the first attempt
+    fn set_consistency(&mut self, consistency: Consistency) {
+        let consistency = Some(stargate_grpc::proto::ConsistencyValue {
+            value: stargate_grpc::Consistency::from(consistency).into(),
+        });
+        match self {
+            Self::Query(query) => {
+                query
+                    .parameters
+                    .get_or_insert_with(Default::default)
+                    .consistency = consistency
+            }
+            Self::Batch(batch) => {
+                batch
+                    .parameters
+                    .get_or_insert_with(Default::default)
+                    .consistency = consistency
+            }
+        }
+    }
+}
+
+impl From<tonic::Status> for QueryError {
+    fn from(status: tonic::Status) -> Self {
+        match status.code() {
+            tonic::Code::Unavailable => Self::Unavailable,
+            tonic::Code::ResourceExhausted => Self::Overloaded,
+            tonic::Code::DeadlineExceeded => Self::ServerTimeout,
+            _ => Self::Server(status.message().to_string()),
+        }
+    }
+}
+
+impl From<BatchType> for stargate_grpc::proto::batch::Type {
+    fn from(batch_type: BatchType) -> Self {
+        match batch_type {
+            BatchType::Logged => Self::Logged,
+            BatchType::Unlogged => Self::Unlogged,
+            BatchType::Counter => Self::Counter,
+        }
+    }
+}
 
 #[async_trait::async_trait]
 impl<'a> CqlStore for &'a mut stargate_grpc::StargateClient {
     type Output = tonic::Response<stargate_grpc::proto::Response>;
-    type Statement = AstrStatement;
-    type StoreError = ();
-    type Query = QueryBuilder;
+    type Statement = AstrRequest;
+    type StoreError = QueryError;
+    type Query = AstrQuery;
 
     async fn execute(self, statement: Self::Statement) -> Result<Self::Output, Self::StoreError> {
-        let query = Self::into_query(&self, statement).await.build();
-        self.execute_query(query).await.map_err(|_| ())
+        let (execution, consistency) = statement.execution();
+        let mut attempts = Attempts::new(&execution, consistency);
+        let mut query = Self::into_query(&self, statement).await?;
+        loop {
+            if let Some(consistency) = attempts.consistency() {
+                query.set_consistency(consistency);
+            }
+            let (client, request) = (&mut *self, query.clone());
+            let result = attempts
+                .timed(async move {
+                    match request {
+                        AstrQuery::Query(query) => client.execute_query(query).await,
+                        AstrQuery::Batch(batch) => client.execute_batch(batch).await,
+                    }
+                    .map_err(QueryError::from)
+                })
+                .await;
+            match result {
+                Err(error) if attempts.retry(&error).await => continue,
+                result => return result,
+            }
+        }
     }
 
-    async fn into_query(&self, statement: Self::Statement) -> Self::Query {
-        let mut query = Query::builder()
-            .keyspace(statement.keyspace)
-            .query(statement.query_str.as_str());
+    async fn into_query(
+        &self,
+        statement: Self::Statement,
+    ) -> Result<Self::Query, Self::StoreError> {
+        Ok(match statement {
+            AstrRequest::Query(statement) => {
+                let mut query = Query::builder()
+                    .keyspace(statement.keyspace)
+                    .query(&statement.query_str);
 
-        let mut enumer = statement.binds.into_iter();
-        for (el, value) in enumer.next() {
-            query = query.bind_name(el.as_str(), value.into());
-        }
-        query
+                if let Some(consistency) = statement.consistency {
+                    query = query.consistency(consistency.into());
+                }
+                if let Some(serial_consistency) = statement.serial_consistency {
+                    query = query.serial_consistency(serial_consistency.into());
+                }
+                if let Some(page_size) = statement.page_size {
+                    query = query.page_size(driver_page_size(page_size));
+                }
+                if let Some(paging_state) = statement.paging_state {
+                    query = query.paging_state(paging_state);
+                }
+
+                for (el, value) in statement.binds {
+                    let value = value.try_into_value().map_err(|_e| QueryError::Encode)?;
+                    query = query.bind_name(el.as_str(), value);
+                }
+                AstrQuery::Query(query.build())
+            }
+            AstrRequest::Batch(statement) => {
+                let mut batch = stargate_grpc::Batch::builder();
+                for (query_string, values) in statement.statements {
+                    batch = batch.query(&query_string);
+                    for (el, value) in values {
+                        let value = Value::try_from(value).map_err(|_e| QueryError::Encode)?;
+                        batch = batch.bind_name(el.as_str(), value);
+                    }
+                }
+                let mut batch = batch.build();
+                batch.set_type(statement.batch_type.into());
+                let parameters = batch.parameters.get_or_insert_with(Default::default);
+                parameters.timestamp = statement.timestamp;
+                parameters.consistency = statement.consistency.map(|consistency| {
+                    stargate_grpc::proto::ConsistencyValue {
+                        value: stargate_grpc::Consistency::from(consistency).into(),
+                    }
+                });
+                AstrQuery::Batch(batch)
+            }
+        })
     }
 }
 
 pub struct AstrStatement {
-    query_str: String,
+    query_str: QueryString,
     binds: Vec<(String, Box<dyn IntoValue + Send>)>,
     keyspace: &'static str,
+    serial_consistency: Option<SerialConsistency>,
+    consistency: Option<Consistency>,
+    page_size: Option<NonZeroU32>,
+    // resumes from the page after the one that returned this state
+    paging_state: Option<Vec<u8>>,
+    execution: Execution,
 }
 
 impl AstrStatement {
     pub fn new(
-        query_str: String,
+        query_str: QueryString,
         binds: Vec<(String, Box<dyn IntoValue + Send>)>,
         keyspace: &'static str,
     ) -> Self {
@@ -46,6 +194,59 @@ impl AstrStatement {
             query_str,
             binds,
             keyspace,
+            serial_consistency: None,
+            consistency: None,
+            page_size: None,
+            paging_state: None,
+            execution: Execution::default(),
+        }
+    }
+
+    fn with_paging(mut self, page_size: Option<NonZeroU32>, paging_state: Option<Vec<u8>>) -> Self {
+        self.page_size = page_size;
+        self.paging_state = paging_state;
+        self
+    }
+
+    fn with_serial_consistency(mut self, serial_consistency: Option<SerialConsistency>) -> Self {
+        self.serial_consistency = serial_consistency;
+        self
+    }
+
+    fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
+}
+
+impl From<Consistency> for stargate_grpc::Consistency {
+    fn from(consistency: Consistency) -> Self {
+        match consistency {
+            Consistency::Any => Self::Any,
+            Consistency::One => Self::One,
+            Consistency::Two => Self::Two,
+            Consistency::Three => Self::Three,
+            Consistency::Quorum => Self::Quorum,
+            Consistency::All => Self::All,
+            Consistency::LocalQuorum => Self::LocalQuorum,
+            Consistency::EachQuorum => Self::EachQuorum,
+            Consistency::LocalOne => Self::LocalOne,
+            Consistency::Serial => Self::Serial,
+            Consistency::LocalSerial => Self::LocalSerial,
+        }
+    }
+}
+
+impl From<SerialConsistency> for stargate_grpc::Consistency {
+    fn from(serial_consistency: SerialConsistency) -> Self {
+        match serial_consistency {
+            SerialConsistency::Serial => Self::Serial,
+            SerialConsistency::LocalSerial => Self::LocalSerial,
         }
     }
 }
@@ -60,12 +261,16 @@ impl<'b, T: NoSql + Send> QueryInterface<&'b mut stargate_grpc::StargateClient>
     ) -> Result<Self::Output, QueryError> {
         let statement =
             <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_statement(self);
-        let result = <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement)
-            .await
-            .map_err(|_e| QueryError::E01)?; //TODO: add error context here
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement).await?;
 
-        <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_output(result)
-            .ok_or(QueryError::E02) // TODO: add error context here
+        let result_set: stargate_grpc::ResultSet =
+            result.try_into().map_err(|_e| QueryError::E02)?;
+        let row = result_set
+            .to_row_iter()
+            .next()
+            .ok_or(QueryError::NotFound)?;
+        T::from_cql(&CqlType::Row(row)).map_err(|_e| QueryError::Decode)
     }
 
     fn into_output(
@@ -82,13 +287,247 @@ impl<'b, T: NoSql + Send> QueryInterface<&'b mut stargate_grpc::StargateClient>
     }
 
     fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
-        let mut res_binds: Vec<(String, Box<dyn IntoValue + Send + 'static>)> =
-            Vec::with_capacity(self.binds.len());
-        let _ = self
-            .binds
-            .into_iter()
-            .map(|(key, val)| res_binds.push((key, Box::new(val))));
-        AstrStatement::new(self.query, res_binds, T::keyspace()) // TODO generate query string in query object
+        AstrStatement::new(self.query, into_binds(self.binds), T::keyspace())
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into() // TODO generate query string in query object
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T: NoSql + Send> QueryInterface<&'b mut stargate_grpc::StargateClient> for FindAll<T> {
+    async fn execute(
+        self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> Result<Self::Output, QueryError> {
+        let statement =
+            <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_statement(self);
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement).await?;
+
+        // a row that fails to decode fails the query instead of being dropped
+        let result_set: stargate_grpc::ResultSet =
+            result.try_into().map_err(|_e| QueryError::E02)?;
+        result_set
+            .to_row_iter()
+            .map(|row| T::from_cql(&CqlType::Row(row)).map_err(|_e| QueryError::Decode))
+            .collect()
+    }
+
+    fn into_output(
+        query_output: <&'b mut stargate_grpc::StargateClient as CqlStore>::Output,
+    ) -> Option<Self::Output> {
+        query_output
+            .try_into()
+            .map(|r: stargate_grpc::ResultSet| r)
+            .ok()?
+            .to_row_iter()
+            .map(|row| T::from_cql(&CqlType::Row(row)).ok())
+            .collect()
+    }
+
+    fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
+        AstrStatement::new(self.query, into_binds(self.binds), T::keyspace())
+            .with_paging(self.page_size, None)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
+    }
+}
+
+fn into_binds(binds: CqlMap) -> Vec<(String, Box<dyn IntoValue + Send + 'static>)> {
+    binds
+        .into_iter()
+        .map(|(key, val)| (key, Box::new(val) as Box<dyn IntoValue + Send + 'static>))
+        .collect()
+}
+
+fn decoded_rows<'s, R: FromCqlData + Send + 's>(
+    store: &'s mut stargate_grpc::StargateClient,
+    keyspace: &'static str,
+    query: QueryString,
+    binds: CqlMap,
+    page_size: Option<NonZeroU32>,
+    consistency: Option<Consistency>,
+    execution: Execution,
+) -> BoxStream<'s, Result<R, QueryError>> {
+    // every page goes through `CqlStore::execute` and is retried with the policy of the query
+    // `None` once the last page was fetched, `Some(None)` for the first page
+    futures::stream::try_unfold((store, Some(None)), move |(store, paging_state)| {
+        let statement = paging_state.map(|paging_state| {
+            AstrStatement::new(query.clone(), into_binds(binds.clone()), keyspace)
+                .with_paging(page_size, paging_state)
+                .with_consistency(consistency)
+                .with_execution(execution.clone())
+        });
+        async move {
+            let Some(statement) = statement else {
+                return Ok::<_, QueryError>(None);
+            };
+            let result = <&mut stargate_grpc::StargateClient as CqlStore>::execute(
+                &mut *store,
+                statement.into(),
+            )
+            .await?;
+            let mut result_set: stargate_grpc::ResultSet =
+                result.try_into().map_err(|_e| QueryError::E02)?;
+            let next = result_set.paging_state.take().map(Some);
+            let rows = result_set
+                .to_row_iter()
+                .map(|row| R::from_cql(&CqlType::Row(row)).map_err(|_e| QueryError::Decode))
+                .collect::<Vec<Result<R, QueryError>>>();
+            Ok(Some((futures::stream::iter(rows), (store, next))))
+        }
+    })
+    .try_flatten()
+    .boxed()
+}
+
+impl<'b, T: NoSql + Send> QueryStream<&'b mut stargate_grpc::StargateClient> for FindAll<T> {
+    type Item = T;
+
+    fn stream<'s>(
+        self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> BoxStream<'s, Result<Self::Item, QueryError>>
+    where
+        &'b mut stargate_grpc::StargateClient: 's,
+        Self: 's,
+    {
+        decoded_rows(
+            store,
+            T::keyspace(),
+            self.query,
+            self.binds,
+            self.page_size,
+            self.consistency,
+            self.execution,
+        )
+    }
+}
+
+impl<'b, T: NoSql> QueryStream<&'b mut stargate_grpc::StargateClient> for DistinctKeys<T>
+where
+    T::Key: Send,
+{
+    type Item = T::Key;
+
+    fn stream<'s>(
+        self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> BoxStream<'s, Result<Self::Item, QueryError>>
+    where
+        &'b mut stargate_grpc::StargateClient: 's,
+        Self: 's,
+    {
+        decoded_rows(
+            store,
+            T::keyspace(),
+            self.query,
+            CqlMap::new(),
+            self.page_size,
+            self.consistency,
+            self.execution,
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T: NoSql + Send> QueryInterface<&'b mut stargate_grpc::StargateClient> for FindPage<T> {
+    async fn execute(
+        mut self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> Result<Self::Output, QueryError> {
+        let fingerprint = self.fingerprint();
+        let paging_state = self.paging_state()?;
+        let statement = AstrStatement::new(
+            self.query.query,
+            into_binds(self.query.binds),
+            T::keyspace(),
+        )
+        .with_paging(Some(self.page_size), paging_state)
+        .with_consistency(self.query.consistency)
+        .with_execution(self.query.execution);
+
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement.into())
+                .await?;
+        let mut result_set: stargate_grpc::ResultSet =
+            result.try_into().map_err(|_e| QueryError::E02)?;
+
+        let next = result_set
+            .paging_state
+            .take()
+            .map(|paging_state| Cursor::new(fingerprint, paging_state));
+        let items = result_set
+            .to_row_iter()
+            .map(|row| T::from_cql(&CqlType::Row(row)).ok())
+            .collect::<Option<Vec<T>>>()
+            .ok_or(QueryError::Decode)?;
+        Ok(Page { items, next })
+    }
+
+    // the cursor of the next page is attached by `execute`
+    fn into_output(
+        query_output: <&'b mut stargate_grpc::StargateClient as CqlStore>::Output,
+    ) -> Option<Self::Output> {
+        let items = query_output
+            .try_into()
+            .map(|r: stargate_grpc::ResultSet| r)
+            .ok()?
+            .to_row_iter()
+            .map(|row| T::from_cql(&CqlType::Row(row)).ok())
+            .collect::<Option<Vec<T>>>()?;
+        Some(Page { items, next: None })
+    }
+
+    fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
+        AstrStatement::new(
+            self.query.query,
+            into_binds(self.query.binds),
+            T::keyspace(),
+        )
+        .with_paging(Some(self.page_size), None)
+        .with_consistency(self.query.consistency)
+        .with_execution(self.query.execution)
+        .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T: NoSql + Send, O: FromCqlData + Send>
+    QueryInterface<&'b mut stargate_grpc::StargateClient> for Aggregate<T, O>
+{
+    async fn execute(
+        self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> Result<Self::Output, QueryError> {
+        let statement =
+            <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_statement(self);
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_output(result)
+            .ok_or(QueryError::Decode)
+    }
+
+    fn into_output(
+        query_output: <&'b mut stargate_grpc::StargateClient as CqlStore>::Output,
+    ) -> Option<Self::Output> {
+        let row = query_output
+            .try_into()
+            .map(|r: stargate_grpc::ResultSet| r)
+            .ok()?
+            .to_row_iter()
+            .next()?;
+        Self::from_row(row)
+    }
+
+    fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
+        AstrStatement::new(self.query, into_binds(self.binds), T::keyspace())
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
     }
 }
 
@@ -100,9 +539,8 @@ impl<'b, T: NoSql + Send> QueryInterface<&'b mut stargate_grpc::StargateClient>
     ) -> Result<Self::Output, QueryError> {
         let statement =
             <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_statement(self);
-        let result = <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement)
-            .await
-            .map_err(|_e| QueryError::E01)?; //TODO: add error context here
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement).await?;
 
         <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_output(result)
             .ok_or(QueryError::E02) // TODO: add error context here
@@ -115,16 +553,143 @@ impl<'b, T: NoSql + Send> QueryInterface<&'b mut stargate_grpc::StargateClient>
     }
 
     fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
-        if let CqlType::Row(bind_map) = T::to_cql(self.model) {
-            let mut res_binds: Vec<(String, Box<dyn IntoValue + Send + 'static>)> =
-                Vec::with_capacity(bind_map.len());
-            let _ = bind_map
-                .into_iter()
-                .map(|(key, val)| res_binds.push((key, Box::new(val))));
-            AstrStatement::new(T::insert_statement().to_string(), res_binds, T::keyspace())
-        } else {
-            panic!("fix me")
-        }
+        // derived models are always rows, anything else leaves the insert
+        // without values and it's rejected by the server
+        let mut bind_map = match T::to_cql(self.model) {
+            CqlType::Row(bind_map) => bind_map,
+            _ => CqlMap::new(),
+        };
+        bind_map.extend(self.binds);
+        AstrStatement::new(self.query, into_binds(bind_map), T::keyspace())
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
         // TODO generate query string in query object
     }
 }
+
+#[async_trait::async_trait]
+impl<'b, T: NoSql + Send> QueryInterface<&'b mut stargate_grpc::StargateClient> for Delete<T> {
+    async fn execute(
+        self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> Result<Self::Output, QueryError> {
+        let statement =
+            <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_statement(self);
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_output(result)
+            .ok_or(QueryError::E02)
+    }
+
+    fn into_output(
+        _query_output: <&'b mut stargate_grpc::StargateClient as CqlStore>::Output,
+    ) -> Option<Self::Output> {
+        Some(true)
+    }
+
+    fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
+        AstrStatement::new(self.query, into_binds(self.binds), T::keyspace())
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T, U> QueryInterface<&'b mut stargate_grpc::StargateClient> for Update<T, U>
+where
+    T: NoSql + Send,
+    U: ToCqlRow<Output = CqlMapWithQuery> + Send,
+{
+    async fn execute(
+        self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> Result<Self::Output, QueryError> {
+        let statement =
+            <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_statement(self);
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_output(result)
+            .ok_or(QueryError::E02)
+    }
+
+    fn into_output(
+        _query_output: <&'b mut stargate_grpc::StargateClient as CqlStore>::Output,
+    ) -> Option<Self::Output> {
+        Some(true)
+    }
+
+    fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
+        let (consistency, execution) = (self.consistency, self.execution.clone());
+        let (query, binds) = self.into_query();
+        AstrStatement::new(query, into_binds(binds), T::keyspace())
+            .with_consistency(consistency)
+            .with_execution(execution)
+            .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T: NoSql + Send> QueryInterface<&'b mut stargate_grpc::StargateClient> for Lwt<T> {
+    async fn execute(
+        self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> Result<Self::Output, QueryError> {
+        let statement =
+            <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_statement(self);
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_output(result)
+            .ok_or(QueryError::E02)
+    }
+
+    fn into_output(
+        query_output: <&'b mut stargate_grpc::StargateClient as CqlStore>::Output,
+    ) -> Option<Self::Output> {
+        let row = query_output
+            .try_into()
+            .map(|r: stargate_grpc::ResultSet| r)
+            .ok()?
+            .to_row_iter()
+            .next()?;
+        LwtResult::from_row(row)
+    }
+
+    fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
+        AstrStatement::new(self.query, into_binds(self.binds), T::keyspace())
+            .with_serial_consistency(self.serial_consistency)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b> QueryInterface<&'b mut stargate_grpc::StargateClient> for Batch {
+    async fn execute(
+        self,
+        store: &'b mut stargate_grpc::StargateClient,
+    ) -> Result<Self::Output, QueryError> {
+        let statement =
+            <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_statement(self);
+        let result =
+            <&'b mut stargate_grpc::StargateClient as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b mut stargate_grpc::StargateClient>>::into_output(result)
+            .ok_or(QueryError::E02)
+    }
+
+    fn into_output(
+        _query_output: <&'b mut stargate_grpc::StargateClient as CqlStore>::Output,
+    ) -> Option<Self::Output> {
+        Some(true)
+    }
+
+    fn into_statement(self) -> <&'b mut stargate_grpc::StargateClient as CqlStore>::Statement {
+        AstrRequest::Batch(self)
+    }
+}
@@ -1 +1,4 @@
+pub mod row;
 pub mod scylla_client;
+pub mod session;
+pub mod statement_cache;
@@ -0,0 +1,119 @@
+// rows of the scylla client encoded without going through a `CqlMap`, models get
+// `SerializeRow` and `DeserializeRow` from `#[sin::nosql]`, code generated by sin refers
+// to the items of this module by their full path
+use crate::data_types::types::CqlMap;
+
+pub use scylla::{
+    deserialize::{
+        row::ColumnIterator, DeserializationError, DeserializeRow, DeserializeValue, TypeCheckError,
+    },
+    frame::response::result::{ColumnSpec, ColumnType},
+    serialize::{
+        row::{RowSerializationContext, SerializeRow},
+        value::SerializeValue,
+        writers::RowWriter,
+        SerializationError,
+    },
+};
+
+#[derive(Debug, thiserror::Error)]
+enum RowError {
+    #[error("no value bound to `{0}`")]
+    MissingBind(String),
+    #[error("column `{0}` is missing")]
+    MissingColumn(&'static str),
+}
+
+pub fn missing_column(column: &'static str) -> DeserializationError {
+    DeserializationError::new(RowError::MissingColumn(column))
+}
+
+// values of the columns of a struct, by column name
+pub trait SerializeColumns {
+    // writes the value of `column`, `None` if it isn't one of the columns
+    fn serialize_column(
+        &self,
+        column: &str,
+        typ: &ColumnType<'_>,
+        writer: &mut RowWriter<'_>,
+    ) -> Option<Result<(), SerializationError>>;
+
+    fn is_empty(&self) -> bool;
+}
+
+impl<R: SerializeColumns + ?Sized> SerializeColumns for &R {
+    fn serialize_column(
+        &self,
+        column: &str,
+        typ: &ColumnType<'_>,
+        writer: &mut RowWriter<'_>,
+    ) -> Option<Result<(), SerializationError>> {
+        (**self).serialize_column(column, typ, writer)
+    }
+
+    fn is_empty(&self) -> bool {
+        (**self).is_empty()
+    }
+}
+
+// columns of both, `A` is looked up first
+impl<A: SerializeColumns, B: SerializeColumns> SerializeColumns for (A, B) {
+    fn serialize_column(
+        &self,
+        column: &str,
+        typ: &ColumnType<'_>,
+        writer: &mut RowWriter<'_>,
+    ) -> Option<Result<(), SerializationError>> {
+        self.0
+            .serialize_column(column, typ, writer)
+            .or_else(|| self.1.serialize_column(column, typ, writer))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty() && self.1.is_empty()
+    }
+}
+
+// binds of filters and USING clauses, keyed by bind marker
+impl SerializeColumns for CqlMap {
+    fn serialize_column(
+        &self,
+        column: &str,
+        typ: &ColumnType<'_>,
+        writer: &mut RowWriter<'_>,
+    ) -> Option<Result<(), SerializationError>> {
+        let value = self.get(column)?;
+        Some(SerializeValue::serialize(value, typ, writer.make_cell_writer()).map(|_proof| ()))
+    }
+
+    fn is_empty(&self) -> bool {
+        Self::is_empty(self)
+    }
+}
+
+// a row with a value for every bind marker of the statement, in the order of the markers
+// e.g. `Columns((&update, key))` for `UPDATE .. SET .. WHERE ..`
+pub struct Columns<R>(pub R);
+
+impl<R: SerializeColumns> SerializeRow for Columns<R> {
+    fn serialize(
+        &self,
+        ctx: &RowSerializationContext<'_>,
+        writer: &mut RowWriter<'_>,
+    ) -> Result<(), SerializationError> {
+        for column in ctx.columns() {
+            self.0
+                .serialize_column(column.name(), column.typ(), writer)
+                .unwrap_or_else(|| {
+                    Err(SerializationError::new(RowError::MissingBind(
+                        column.name().to_owned(),
+                    )))
+                })?;
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
@@ -1,35 +1,268 @@
-use std::collections::HashMap;
-
+use super::{
+    row::{
+        missing_column, ColumnIterator, ColumnSpec, Columns, DeserializationError, DeserializeRow,
+        DeserializeValue, SerializeColumns, TypeCheckError,
+    },
+    session::ScyllaSession,
+    statement_cache::StatementCache,
+};
 use crate::{
-    data_types::types::{CqlMap, CqlType},
+    data_types::types::{Consistency, CqlMap, FromCqlData, QueryString, SerialConsistency},
     nosql::interface::{CqlStore, NoSql},
-    query::query::{Create, FindAll, FindOne, QueryError, QueryInterface},
+    query::{
+        execution::{Attempts, Execution},
+        page::{driver_page_size, Cursor, FindPage, Page},
+        query::{
+            Aggregate, Batch, BatchType, Create, Delete, DistinctKeys, FindAll, FindOne, Lwt,
+            LwtResult, QueryError, QueryInterface, QueryStream, Update,
+        },
+    },
 };
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
 use scylla::{
-    prepared_statement::PreparedStatement, serialize::value::SerializeValue, QueryResult, Session,
+    prepared_statement::PreparedStatement,
+    serialize::{
+        row::{RowSerializationContext, SerializeRow},
+        writers::RowWriter,
+        SerializationError,
+    },
+    statement::{PagingState, PagingStateResponse},
+    transport::retry_policy::{FallthroughRetryPolicy, RetryPolicy},
+    QueryResult,
 };
+use std::{num::NonZeroU32, ops::ControlFlow, sync::Arc};
+
+// values of a statement, models are bound without being converted into a map
+pub enum Binds {
+    Map(CqlMap),
+    Row(Box<dyn SerializeRow + Send + Sync>),
+}
+
+impl From<CqlMap> for Binds {
+    fn from(binds: CqlMap) -> Self {
+        Self::Map(binds)
+    }
+}
+
+impl SerializeRow for Binds {
+    fn serialize(
+        &self,
+        ctx: &RowSerializationContext<'_>,
+        writer: &mut RowWriter<'_>,
+    ) -> Result<(), SerializationError> {
+        match self {
+            Self::Map(binds) => SerializeRow::serialize(binds, ctx, writer),
+            Self::Row(row) => row.serialize(ctx, writer),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Map(binds) => binds.is_empty(),
+            Self::Row(row) => row.is_empty(),
+        }
+    }
+}
 
 pub struct ScyllaQuery {
-    query_string: String,
-    pub binds: HashMap<String, CqlType>,
+    query_string: QueryString,
+    pub binds: Binds,
+    serial_consistency: Option<SerialConsistency>,
+    consistency: Option<Consistency>,
+    execution: Execution,
 }
 
 impl ScyllaQuery {
-    fn new(query_string: String, binds: HashMap<String, CqlType>) -> Self {
+    fn new(query_string: QueryString, binds: impl Into<Binds>) -> Self {
         Self {
             query_string,
-            binds,
+            binds: binds.into(),
+            serial_consistency: None,
+            consistency: None,
+            execution: Execution::default(),
+        }
+    }
+
+    fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
+
+    fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    fn with_serial_consistency(mut self, serial_consistency: Option<SerialConsistency>) -> Self {
+        self.serial_consistency = serial_consistency;
+        self
+    }
+}
+
+impl From<Consistency> for scylla::statement::Consistency {
+    fn from(consistency: Consistency) -> Self {
+        match consistency {
+            Consistency::Any => Self::Any,
+            Consistency::One => Self::One,
+            Consistency::Two => Self::Two,
+            Consistency::Three => Self::Three,
+            Consistency::Quorum => Self::Quorum,
+            Consistency::All => Self::All,
+            Consistency::LocalQuorum => Self::LocalQuorum,
+            Consistency::EachQuorum => Self::EachQuorum,
+            Consistency::LocalOne => Self::LocalOne,
+            Consistency::Serial => Self::Serial,
+            Consistency::LocalSerial => Self::LocalSerial,
+        }
+    }
+}
+
+impl From<scylla::transport::errors::QueryError> for QueryError {
+    fn from(error: scylla::transport::errors::QueryError) -> Self {
+        use scylla::transport::errors::{DbError, QueryError as ScyllaError};
+        match error {
+            ScyllaError::DbError(DbError::Unavailable { .. }, _) => Self::Unavailable,
+            ScyllaError::DbError(DbError::Overloaded, _) => Self::Overloaded,
+            ScyllaError::DbError(DbError::ReadTimeout { .. } | DbError::WriteTimeout { .. }, _) => {
+                Self::ServerTimeout
+            }
+            ScyllaError::DbError(_, message) => Self::Server(message),
+            ScyllaError::RequestTimeout(_) => Self::Timeout,
+            error => Self::Driver(error.to_string()),
+        }
+    }
+}
+
+impl From<SerialConsistency> for scylla::statement::SerialConsistency {
+    fn from(serial_consistency: SerialConsistency) -> Self {
+        match serial_consistency {
+            SerialConsistency::Serial => Self::Serial,
+            SerialConsistency::LocalSerial => Self::LocalSerial,
+        }
+    }
+}
+
+pub enum ScyllaStatement {
+    Query(ScyllaQuery),
+    Batch(Batch),
+}
+
+impl ScyllaStatement {
+    fn execution(&self) -> (Execution, Option<Consistency>) {
+        match self {
+            Self::Query(query) => (query.execution.clone(), query.consistency),
+            Self::Batch(batch) => (batch.execution.clone(), batch.consistency),
+        }
+    }
+}
+
+impl From<ScyllaQuery> for ScyllaStatement {
+    fn from(query: ScyllaQuery) -> Self {
+        Self::Query(query)
+    }
+}
+
+impl From<BatchType> for scylla::batch::BatchType {
+    fn from(batch_type: BatchType) -> Self {
+        match batch_type {
+            BatchType::Logged => Self::Logged,
+            BatchType::Unlogged => Self::Unlogged,
+            BatchType::Counter => Self::Counter,
+        }
+    }
+}
+
+pub enum ScyllaPrepared {
+    Statement(ScyllaPreparedStatement),
+    // native batch with the binds and query strings of every statement
+    Batch(scylla::batch::Batch, Vec<CqlMap>, Vec<QueryString>),
+}
+
+impl ScyllaPrepared {
+    // retries can run with a lower consistency than the first attempt
+    fn set_consistency(&mut self, consistency: Consistency) {
+        match self {
+            Self::Statement(statement) => statement
+                .prepared_statement
+                .set_consistency(consistency.into()),
+            Self::Batch(batch, _, _) => batch.set_consistency(consistency.into()),
+        }
+    }
+
+    fn query_strings(&self) -> Vec<&str> {
+        match self {
+            Self::Statement(statement) => vec![statement.prepared_statement.get_statement()],
+            Self::Batch(_, _, query_strings) => query_strings.iter().map(AsRef::as_ref).collect(),
+        }
+    }
+}
+
+// statements unknown to the server were dropped by it, after a restart or a schema change,
+// they are dropped from the cache as well and prepared again on their next use
+fn evict_stale<'q>(
+    statements: &StatementCache,
+    error: scylla::transport::errors::QueryError,
+    query_strings: impl IntoIterator<Item = &'q str>,
+) -> QueryError {
+    use scylla::transport::errors::{DbError, QueryError as ScyllaError};
+    if matches!(error, ScyllaError::DbError(DbError::Unprepared { .. }, _)) {
+        query_strings
+            .into_iter()
+            .for_each(|query_string| statements.invalidate(query_string));
+    }
+    error.into()
+}
+
+// failed attempts are retried by the `RetryPolicy` of the query, the driver doesn't retry on its own
+fn no_driver_retries() -> Option<Arc<dyn RetryPolicy>> {
+    Some(Arc::new(FallthroughRetryPolicy::new()))
+}
+
+async fn prepare(store: &ScyllaSession, query: &str) -> Result<PreparedStatement, QueryError> {
+    let mut prepared_statement = store.statement_cache().prepare(store, query).await?;
+    prepared_statement.set_retry_policy(no_driver_retries());
+    Ok(prepared_statement)
+}
+
+// fetches a single page, retried with the policy of the query
+async fn single_page(
+    store: &ScyllaSession,
+    prepared_statement: &mut PreparedStatement,
+    binds: &CqlMap,
+    paging_state: PagingState,
+    execution: &Execution,
+    consistency: Option<Consistency>,
+) -> Result<(QueryResult, PagingStateResponse), QueryError> {
+    let mut attempts = Attempts::new(execution, consistency);
+    loop {
+        if let Some(consistency) = attempts.consistency() {
+            prepared_statement.set_consistency(consistency.into());
+        }
+        let statement = &*prepared_statement;
+        let result = attempts
+            .timed(async {
+                store
+                    .execute_single_page(statement, binds, paging_state.clone())
+                    .await
+                    .map_err(|error| {
+                        evict_stale(store.statement_cache(), error, [statement.get_statement()])
+                    })
+            })
+            .await;
+        match result {
+            Err(error) if attempts.retry(&error).await => continue,
+            result => return result,
         }
     }
 }
 
 pub struct ScyllaPreparedStatement {
-    binds: HashMap<String, CqlType>,
+    binds: Binds,
     prepared_statement: PreparedStatement,
 }
 
 impl ScyllaPreparedStatement {
-    pub fn new(binds: HashMap<String, CqlType>, prepared_statement: PreparedStatement) -> Self {
+    pub fn new(binds: Binds, prepared_statement: PreparedStatement) -> Self {
         Self {
             binds,
             prepared_statement,
@@ -38,110 +271,479 @@ impl ScyllaPreparedStatement {
 }
 
 #[async_trait::async_trait]
-impl<'a> CqlStore for &'a Session {
+impl<'a> CqlStore for &'a ScyllaSession {
     type Output = QueryResult;
-    type Statement = ScyllaQuery;
-    type StoreError = ();
-    type Query = ScyllaPreparedStatement;
+    type Statement = ScyllaStatement;
+    type StoreError = QueryError;
+    type Query = ScyllaPrepared;
 
     async fn execute(self, statement: Self::Statement) -> Result<Self::Output, Self::StoreError> {
-        let query = self.into_query(statement).await;
-
-        let result = self
-            .execute_unpaged(&query.prepared_statement, query.binds)
-            .await
-            .map_err(|e| {})?; // TODO : manage and propogate errors
-        Ok(result)
+        let (execution, consistency) = statement.execution();
+        let mut attempts = Attempts::new(&execution, consistency);
+        let mut query = self.into_query(statement).await?;
+        loop {
+            if let Some(consistency) = attempts.consistency() {
+                query.set_consistency(consistency);
+            }
+            let result = attempts
+                .timed(async {
+                    match &query {
+                        ScyllaPrepared::Statement(query) => {
+                            self.execute_unpaged(&query.prepared_statement, &query.binds)
+                                .await
+                        }
+                        ScyllaPrepared::Batch(batch, binds, _) => self.batch(batch, binds).await,
+                    }
+                    .map_err(|error| {
+                        evict_stale(self.statement_cache(), error, query.query_strings())
+                    })
+                })
+                .await;
+            match result {
+                Err(error) if attempts.retry(&error).await => continue,
+                result => return result,
+            }
+        }
     }
 
-    async fn into_query(&self, statement: Self::Statement) -> Self::Query {
-        let prepared_statement = self
-            .prepare(statement.query_string)
-            .await
-            .expect("query generation from scylla failed"); //// Prepare statements on all connections concurrently underneath
-        ScyllaPreparedStatement::new(statement.binds, prepared_statement)
+    async fn into_query(
+        &self,
+        statement: Self::Statement,
+    ) -> Result<Self::Query, Self::StoreError> {
+        Ok(match statement {
+            ScyllaStatement::Query(statement) => {
+                // cached statements are copied, options of this query don't leak into the cache
+                let mut prepared_statement = prepare(self, &statement.query_string).await?;
+                if let Some(consistency) = statement.consistency {
+                    prepared_statement.set_consistency(consistency.into());
+                }
+                if let Some(serial_consistency) = statement.serial_consistency {
+                    prepared_statement.set_serial_consistency(Some(serial_consistency.into()));
+                }
+                prepared_statement.set_is_idempotent(statement.execution.idempotent);
+                ScyllaPrepared::Statement(ScyllaPreparedStatement::new(
+                    statement.binds,
+                    prepared_statement,
+                ))
+            }
+            ScyllaStatement::Batch(statement) => {
+                let mut batch = scylla::batch::Batch::new(statement.batch_type.into());
+                batch.set_timestamp(statement.timestamp);
+                if let Some(consistency) = statement.consistency {
+                    batch.set_consistency(consistency.into());
+                }
+                batch.set_is_idempotent(statement.execution.idempotent);
+                batch.set_retry_policy(no_driver_retries());
+                let mut binds = Vec::with_capacity(statement.statements.len());
+                let mut query_strings = Vec::with_capacity(statement.statements.len());
+                for (query_string, values) in statement.statements {
+                    let prepared_statement =
+                        self.statement_cache().prepare(self, &query_string).await?;
+                    batch.append_statement(prepared_statement);
+                    binds.push(values);
+                    query_strings.push(query_string);
+                }
+                ScyllaPrepared::Batch(batch, binds, query_strings)
+            }
+        })
     }
 }
 
 // Query Interface implementation
 #[async_trait::async_trait]
-impl<'b, T: NoSql + Send> QueryInterface<&'b Session> for FindOne<T> {
-    async fn execute(self, store: &'b Session) -> Result<Self::Output, QueryError> {
-        let statement = <Self as QueryInterface<&'b Session>>::into_statement(self);
-        let result = <&'b Session as CqlStore>::execute(store, statement)
-            .await
-            .map_err(|_e| QueryError::E01)?;
+impl<'b, T> QueryInterface<&'b ScyllaSession> for FindOne<T>
+where
+    T: NoSql + for<'f, 'm> DeserializeRow<'f, 'm> + Send,
+{
+    async fn execute(self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let statement = <Self as QueryInterface<&'b ScyllaSession>>::into_statement(self);
+        let result = <&'b ScyllaSession as CqlStore>::execute(store, statement).await?;
 
-        <Self as QueryInterface<&'b Session>>::into_output(result).ok_or(QueryError::E02)
+        let rows = result.into_rows_result().map_err(|_e| QueryError::E02)?;
+        match rows.maybe_first_row::<T>() {
+            Ok(Some(row)) => Ok(row),
+            Ok(None) => Err(QueryError::NotFound),
+            Err(_e) => Err(QueryError::Decode),
+        }
     }
 
-    fn into_output(query_output: <&'b Session as CqlStore>::Output) -> Option<Self::Output> {
+    fn into_output(query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
         let iter = query_output.into_rows_result().ok()?;
-        let res = iter.first_row::<CqlMap>().ok().and_then(
-            |cql_map: std::collections::HashMap<String, CqlType>| {
-                T::from_cql(&CqlType::Row(cql_map)).ok()
-            },
-        )?;
-        Some(res)
+        iter.first_row::<T>().ok()
     }
 
-    fn into_statement(self) -> <&'b Session as CqlStore>::Statement {
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
         ScyllaQuery::new(self.query, self.binds)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
     }
 }
 
 // Query Interface implementation
 #[async_trait::async_trait]
-impl<'b, T: NoSql + Send> QueryInterface<&'b Session> for FindAll<T> {
-    async fn execute(self, store: &'b Session) -> Result<Self::Output, QueryError> {
-        let statement = <Self as QueryInterface<&'b Session>>::into_statement(self);
-        let result = <&'b Session as CqlStore>::execute(store, statement)
-            .await
-            .map_err(|_e| QueryError::E01)?;
+impl<'b, T> QueryInterface<&'b ScyllaSession> for FindAll<T>
+where
+    T: NoSql + for<'f, 'm> DeserializeRow<'f, 'm> + Send,
+{
+    async fn execute(self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let statement = <Self as QueryInterface<&'b ScyllaSession>>::into_statement(self);
+        let result = <&'b ScyllaSession as CqlStore>::execute(store, statement).await?;
 
-        <Self as QueryInterface<&'b Session>>::into_output(result).ok_or(QueryError::E02)
+        // a row that fails to decode fails the query instead of being dropped
+        let rows = result.into_rows_result().map_err(|_e| QueryError::E02)?;
+        rows.rows::<T>()
+            .map_err(|_e| QueryError::Decode)?
+            .map(|row| row.map_err(|_e| QueryError::Decode))
+            .collect()
     }
 
-    fn into_output(query_output: <&'b Session as CqlStore>::Output) -> Option<Self::Output> {
+    fn into_output(query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
         let iter = query_output.into_rows_result().ok()?;
-        iter.rows::<CqlMap>()
-            .ok()?
-            .filter_map(|cql_map| {
-                cql_map
-                    .ok()
-                    .map(|inner| T::from_cql(&CqlType::Row(inner)).ok())
-            })
-            .collect()
+        iter.rows::<T>().ok()?.map(Result::ok).collect()
+    }
+
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
+        ScyllaQuery::new(self.query, self.binds)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
+    }
+}
+
+// every page is fetched with the retry policy of the query, as the stream is polled
+fn decoded_rows<'s, R>(
+    store: &'s ScyllaSession,
+    query: QueryString,
+    binds: CqlMap,
+    page_size: Option<NonZeroU32>,
+    consistency: Option<Consistency>,
+    execution: Execution,
+) -> BoxStream<'s, Result<R, QueryError>>
+where
+    R: for<'f, 'm> DeserializeRow<'f, 'm> + Send + 'static,
+{
+    futures::stream::once(async move {
+        let mut prepared_statement = prepare(store, &query).await?;
+        if let Some(page_size) = page_size {
+            prepared_statement.set_page_size(driver_page_size(page_size));
+        }
+        prepared_statement.set_is_idempotent(execution.idempotent);
+        // `None` once the last page was fetched
+        let pages = futures::stream::try_unfold(
+            (
+                prepared_statement,
+                binds,
+                execution,
+                Some(PagingState::start()),
+            ),
+            move |(mut prepared_statement, binds, execution, paging_state)| async move {
+                let Some(paging_state) = paging_state else {
+                    return Ok::<_, QueryError>(None);
+                };
+                let (result, paging_state_response) = single_page(
+                    store,
+                    &mut prepared_statement,
+                    &binds,
+                    paging_state,
+                    &execution,
+                    consistency,
+                )
+                .await?;
+                let rows = result
+                    .into_rows_result()
+                    .map_err(|_e| QueryError::Decode)?
+                    .rows::<R>()
+                    .map_err(|_e| QueryError::Decode)?
+                    .map(|row| row.map_err(|_e| QueryError::Decode))
+                    .collect::<Vec<Result<R, QueryError>>>();
+                let next = match paging_state_response.into_paging_control_flow() {
+                    ControlFlow::Continue(paging_state) => Some(paging_state),
+                    ControlFlow::Break(()) => None,
+                };
+                Ok(Some((
+                    futures::stream::iter(rows),
+                    (prepared_statement, binds, execution, next),
+                )))
+            },
+        );
+        Ok::<_, QueryError>(pages.try_flatten())
+    })
+    .try_flatten()
+    .boxed()
+}
+
+impl<'b, T> QueryStream<&'b ScyllaSession> for FindAll<T>
+where
+    T: NoSql + for<'f, 'm> DeserializeRow<'f, 'm> + Send + 'static,
+{
+    type Item = T;
+
+    fn stream<'s>(self, store: &'b ScyllaSession) -> BoxStream<'s, Result<Self::Item, QueryError>>
+    where
+        &'b ScyllaSession: 's,
+        Self: 's,
+    {
+        decoded_rows(
+            store,
+            self.query,
+            self.binds,
+            self.page_size,
+            self.consistency,
+            self.execution,
+        )
+    }
+}
+
+impl<'b, T: NoSql> QueryStream<&'b ScyllaSession> for DistinctKeys<T>
+where
+    T::Key: for<'f, 'm> DeserializeRow<'f, 'm> + Send + 'static,
+{
+    type Item = T::Key;
+
+    fn stream<'s>(self, store: &'b ScyllaSession) -> BoxStream<'s, Result<Self::Item, QueryError>>
+    where
+        &'b ScyllaSession: 's,
+        Self: 's,
+    {
+        decoded_rows(
+            store,
+            self.query,
+            CqlMap::new(),
+            self.page_size,
+            self.consistency,
+            self.execution,
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T> QueryInterface<&'b ScyllaSession> for FindPage<T>
+where
+    T: NoSql + for<'f, 'm> DeserializeRow<'f, 'm> + Send,
+{
+    async fn execute(mut self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let fingerprint = self.fingerprint();
+        let paging_state = match self.paging_state()? {
+            Some(paging_state) => PagingState::new_from_raw_bytes(paging_state),
+            None => PagingState::start(),
+        };
+
+        let mut prepared_statement = prepare(store, &self.query.query).await?;
+        prepared_statement.set_page_size(driver_page_size(self.page_size));
+        prepared_statement.set_is_idempotent(self.query.execution.idempotent);
+        let (result, paging_state_response) = single_page(
+            store,
+            &mut prepared_statement,
+            &self.query.binds,
+            paging_state,
+            &self.query.execution,
+            self.query.consistency,
+        )
+        .await?;
+
+        let mut page = <Self as QueryInterface<&'b ScyllaSession>>::into_output(result)
+            .ok_or(QueryError::Decode)?;
+        if let ControlFlow::Continue(paging_state) =
+            paging_state_response.into_paging_control_flow()
+        {
+            page.next = paging_state
+                .as_bytes_slice()
+                .map(|paging_state| Cursor::new(fingerprint, paging_state.to_vec()));
+        }
+        Ok(page)
+    }
+
+    // the cursor of the next page is attached by `execute`
+    fn into_output(query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
+        let items = <FindAll<T> as QueryInterface<&'b ScyllaSession>>::into_output(query_output)?;
+        Some(Page { items, next: None })
     }
 
-    fn into_statement(self) -> <&'b Session as CqlStore>::Statement {
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
+        ScyllaQuery::new(self.query.query, self.query.binds)
+            .with_consistency(self.query.consistency)
+            .with_execution(self.query.execution)
+            .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T: NoSql + Send, O: FromCqlData + Send> QueryInterface<&'b ScyllaSession>
+    for Aggregate<T, O>
+{
+    async fn execute(self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let statement = <Self as QueryInterface<&'b ScyllaSession>>::into_statement(self);
+        let result = <&'b ScyllaSession as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b ScyllaSession>>::into_output(result).ok_or(QueryError::Decode)
+    }
+
+    fn into_output(query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
+        let iter = query_output.into_rows_result().ok()?;
+        Self::from_row(iter.first_row::<CqlMap>().ok()?)
+    }
+
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
         ScyllaQuery::new(self.query, self.binds)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
     }
 }
 
 #[async_trait::async_trait]
-impl<'b, T: NoSql + Send> QueryInterface<&'b Session> for Create<T> {
-    async fn execute(self, store: &'b Session) -> Result<Self::Output, QueryError> {
-        let statement = <Self as QueryInterface<&'b Session>>::into_statement(self);
+impl<'b, T> QueryInterface<&'b ScyllaSession> for Create<T>
+where
+    T: NoSql + SerializeColumns + Send + Sync + 'static,
+{
+    async fn execute(self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let statement = <Self as QueryInterface<&'b ScyllaSession>>::into_statement(self);
 
-        let result = <&'b Session as CqlStore>::execute(store, statement)
-            .await
-            .map_err(|_e| QueryError::E01)?; //TODO: add error context here
+        let result = <&'b ScyllaSession as CqlStore>::execute(store, statement).await?;
 
-        <Self as QueryInterface<&'b Session>>::into_output(result).ok_or(QueryError::E02)
+        <Self as QueryInterface<&'b ScyllaSession>>::into_output(result).ok_or(QueryError::E02)
         // TODO: add error context here
     }
 
-    fn into_output(_query_output: <&'b Session as CqlStore>::Output) -> Option<Self::Output> {
+    fn into_output(_query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
         Some(true) // TODO: verify result
     }
 
-    fn into_statement(self) -> <&'b Session as CqlStore>::Statement {
-        if let CqlType::Row(bind_map) = T::to_cql(self.model) {
-            ScyllaQuery::new(T::insert_statement().to_string(), bind_map)
-        } else {
-            panic!("fix me")
+    // the model is bound column by column, followed by the binds of the USING clause
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
+        let row = Columns((self.model, self.binds));
+        ScyllaQuery::new(self.query, Binds::Row(Box::new(row)))
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T: NoSql + Send> QueryInterface<&'b ScyllaSession> for Delete<T> {
+    async fn execute(self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let statement = <Self as QueryInterface<&'b ScyllaSession>>::into_statement(self);
+
+        let result = <&'b ScyllaSession as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b ScyllaSession>>::into_output(result).ok_or(QueryError::E02)
+    }
+
+    fn into_output(_query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
+        Some(true)
+    }
+
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
+        ScyllaQuery::new(self.query, self.binds)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T, U> QueryInterface<&'b ScyllaSession> for Update<T, U>
+where
+    T: NoSql + Send,
+    U: SerializeColumns + Send + Sync + 'static,
+{
+    async fn execute(self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let statement = <Self as QueryInterface<&'b ScyllaSession>>::into_statement(self);
+
+        let result = <&'b ScyllaSession as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b ScyllaSession>>::into_output(result).ok_or(QueryError::E02)
+    }
+
+    fn into_output(_query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
+        Some(true)
+    }
+
+    // the update struct is bound column by column, followed by the binds of the
+    // WHERE and USING clauses
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
+        let row = Columns((self.set, self.where_binds));
+        ScyllaQuery::new(self.query, Binds::Row(Box::new(row)))
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
+    }
+}
+
+// `[applied]` column of a conditional write, the current row is decoded on its own
+struct Applied(bool);
+
+impl<'frame, 'metadata> DeserializeRow<'frame, 'metadata> for Applied {
+    fn type_check(specs: &[ColumnSpec<'_>]) -> Result<(), TypeCheckError> {
+        match specs.iter().find(|spec| spec.name() == "[applied]") {
+            Some(spec) => bool::type_check(spec.typ()),
+            None => Ok(()),
         }
-        // TODO generate query string in query object
+    }
+
+    fn deserialize(
+        columns: ColumnIterator<'frame, 'metadata>,
+    ) -> Result<Self, DeserializationError> {
+        for column in columns {
+            let column = column?;
+            if column.spec.name() == "[applied]" {
+                return bool::deserialize(column.spec.typ(), column.slice).map(Self);
+            }
+        }
+        Err(missing_column("[applied]"))
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b, T> QueryInterface<&'b ScyllaSession> for Lwt<T>
+where
+    T: NoSql + for<'f, 'm> DeserializeRow<'f, 'm> + Send,
+{
+    async fn execute(self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let statement = <Self as QueryInterface<&'b ScyllaSession>>::into_statement(self);
+
+        let result = <&'b ScyllaSession as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b ScyllaSession>>::into_output(result).ok_or(QueryError::E02)
+    }
+
+    fn into_output(query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
+        let rows = query_output.into_rows_result().ok()?;
+        let Applied(applied) = rows.first_row::<Applied>().ok()?;
+        // only the columns of the condition are returned for `only_if`, which
+        // leaves `current` empty
+        let current = match applied {
+            true => None,
+            false => rows.first_row::<T>().ok(),
+        };
+        Some(LwtResult { applied, current })
+    }
+
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
+        ScyllaQuery::new(self.query, self.binds)
+            .with_serial_consistency(self.serial_consistency)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+            .into()
+    }
+}
+
+#[async_trait::async_trait]
+impl<'b> QueryInterface<&'b ScyllaSession> for Batch {
+    async fn execute(self, store: &'b ScyllaSession) -> Result<Self::Output, QueryError> {
+        let statement = <Self as QueryInterface<&'b ScyllaSession>>::into_statement(self);
+
+        let result = <&'b ScyllaSession as CqlStore>::execute(store, statement).await?;
+
+        <Self as QueryInterface<&'b ScyllaSession>>::into_output(result).ok_or(QueryError::E02)
+    }
+
+    fn into_output(_query_output: <&'b ScyllaSession as CqlStore>::Output) -> Option<Self::Output> {
+        Some(true)
+    }
+
+    fn into_statement(self) -> <&'b ScyllaSession as CqlStore>::Statement {
+        ScyllaStatement::Batch(self)
     }
 }
@@ -0,0 +1,48 @@
+use super::statement_cache::StatementCache;
+use scylla::{transport::errors::QueryError, Session};
+
+// a session with its own cache of prepared statements, queries of the scylla client run on it
+pub struct ScyllaSession {
+    session: Session,
+    statements: StatementCache,
+}
+
+impl ScyllaSession {
+    pub fn new(session: Session) -> Self {
+        Self::with_capacity(session, StatementCache::DEFAULT_CAPACITY)
+    }
+
+    // a capacity of 0 disables the statement cache
+    pub fn with_capacity(session: Session, capacity: usize) -> Self {
+        Self {
+            session,
+            statements: StatementCache::new(capacity),
+        }
+    }
+
+    pub fn statement_cache(&self) -> &StatementCache {
+        &self.statements
+    }
+
+    // waits for the nodes to agree on the schema, statements prepared against an older
+    // schema are dropped and prepared again on their next use
+    pub async fn await_schema_agreement(&self) -> Result<(), QueryError> {
+        let schema_version = self.session.await_schema_agreement().await?;
+        self.statements.schema_agreed(schema_version.as_u128());
+        Ok(())
+    }
+}
+
+impl From<Session> for ScyllaSession {
+    fn from(session: Session) -> Self {
+        Self::new(session)
+    }
+}
+
+impl std::ops::Deref for ScyllaSession {
+    type Target = Session;
+
+    fn deref(&self) -> &Self::Target {
+        &self.session
+    }
+}
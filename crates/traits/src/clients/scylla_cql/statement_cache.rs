@@ -0,0 +1,201 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use scylla::{prepared_statement::PreparedStatement, transport::errors::QueryError, Session};
+
+// least recently used entries are evicted first
+struct Lru<V> {
+    capacity: usize,
+    // incremented on every use
+    tick: u64,
+    entries: HashMap<String, (V, u64)>,
+    // queries by their last use
+    order: BTreeMap<u64, String>,
+}
+
+impl<V: Clone> Lru<V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    fn get(&mut self, query: &str) -> Option<V> {
+        let (value, used) = self.entries.get_mut(query)?;
+        self.tick += 1;
+        if let Some(query) = self.order.remove(&*used) {
+            self.order.insert(self.tick, query);
+        }
+        *used = self.tick;
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, query: String, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(&query);
+        self.evict(self.capacity - 1);
+        self.tick += 1;
+        self.order.insert(self.tick, query.clone());
+        self.entries.insert(query, (value, self.tick));
+    }
+
+    fn remove(&mut self, query: &str) {
+        if let Some((_, used)) = self.entries.remove(query) {
+            self.order.remove(&used);
+        }
+    }
+
+    // drops the least recently used entries until `len` are left
+    fn evict(&mut self, len: usize) {
+        while self.entries.len() > len {
+            let Some((_, query)) = self.order.pop_first() else {
+                break;
+            };
+            self.entries.remove(&query);
+        }
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict(capacity);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+struct Entries {
+    statements: Lru<PreparedStatement>,
+    // schema the statements were prepared against, as agreed by the nodes
+    schema_version: Option<u128>,
+}
+
+// prepared statements of a session, by query string
+// the driver re-prepares statements on connections that haven't seen them
+pub struct StatementCache {
+    entries: Mutex<Entries>,
+}
+
+impl StatementCache {
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(Entries {
+                statements: Lru::new(capacity),
+                schema_version: None,
+            }),
+        }
+    }
+
+    // a capacity of 0 disables the cache
+    pub fn set_capacity(&self, capacity: usize) {
+        self.entries().statements.set_capacity(capacity);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().statements.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn invalidate(&self, query: &str) {
+        self.entries().statements.remove(query);
+    }
+
+    // to be called after schema changes, statements are prepared again on their next use
+    pub fn clear(&self) {
+        self.entries().statements.clear();
+    }
+
+    // statements prepared against another schema are dropped
+    pub(crate) fn schema_agreed(&self, schema_version: u128) {
+        let mut entries = self.entries();
+        if entries.schema_version != Some(schema_version) {
+            entries.statements.clear();
+            entries.schema_version = Some(schema_version);
+        }
+    }
+
+    pub(crate) async fn prepare(
+        &self,
+        session: &Session,
+        query: &str,
+    ) -> Result<PreparedStatement, QueryError> {
+        if let Some(statement) = self.entries().statements.get(query) {
+            return Ok(statement);
+        }
+        // concurrent misses of the same query all prepare it, the last one is kept
+        let statement = session.prepare(query).await?;
+        self.entries()
+            .statements
+            .insert(query.to_owned(), statement.clone());
+        Ok(statement)
+    }
+
+    // the lock is never held across an await
+    fn entries(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lru;
+
+    fn filled(capacity: usize, queries: &[&str]) -> Lru<usize> {
+        let mut lru = Lru::new(capacity);
+        for (value, query) in queries.iter().enumerate() {
+            lru.insert((*query).to_owned(), value);
+        }
+        lru
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut lru = filled(2, &["a", "b"]);
+        assert_eq!(lru.get("a"), Some(0));
+        lru.insert("c".to_owned(), 2);
+        assert_eq!(lru.get("b"), None);
+        assert_eq!(lru.get("a"), Some(0));
+        assert_eq!(lru.get("c"), Some(2));
+    }
+
+    #[test]
+    fn reinserts_without_evicting() {
+        let mut lru = filled(2, &["a", "b"]);
+        lru.insert("a".to_owned(), 2);
+        assert_eq!(lru.entries.len(), 2);
+        assert_eq!(lru.get("a"), Some(2));
+        assert_eq!(lru.get("b"), Some(1));
+    }
+
+    #[test]
+    fn shrinks_to_capacity() {
+        let mut lru = filled(3, &["a", "b", "c"]);
+        lru.set_capacity(1);
+        assert_eq!(lru.entries.len(), 1);
+        assert_eq!(lru.get("c"), Some(2));
+    }
+
+    #[test]
+    fn zero_capacity_disables_cache() {
+        let mut lru = filled(2, &["a", "b"]);
+        lru.set_capacity(0);
+        assert_eq!(lru.entries.len(), 0);
+        assert_eq!(lru.order.len(), 0);
+        lru.insert("c".to_owned(), 2);
+        assert_eq!(lru.get("c"), None);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
 #[derive(Debug, Clone)]
 pub enum CqlType {
@@ -9,12 +9,14 @@ pub enum CqlType {
     NumFloat(f64),
     Timestamp(time::OffsetDateTime),
     Bytes(Vec<u8>),
+    List(Vec<Self>),
     Null,
 }
 
 pub type CqlMap = HashMap<String, CqlType>;
 pub type CqlMapWithQuery = (QueryString, HashMap<String, CqlType>);
-type QueryString = String;
+// query strings generated by the `nosql` macro are borrowed
+pub type QueryString = Cow<'static, str>;
 
 pub trait ToCqlData {
     fn to_cql(self) -> CqlType;
@@ -29,19 +31,19 @@ pub trait ToCqlRow {
 
 impl ToCqlData for String {
     fn to_cql(self) -> CqlType {
-        CqlType::Str(self.clone())
+        CqlType::Str(self)
     }
 }
 
 impl ToCqlData for i64 {
     fn to_cql(self) -> CqlType {
-        CqlType::NumInt(self.clone())
+        CqlType::NumInt(self)
     }
 }
 
 impl ToCqlData for f64 {
     fn to_cql(self) -> CqlType {
-        CqlType::NumFloat(self.clone())
+        CqlType::NumFloat(self)
     }
 }
 
@@ -51,6 +53,12 @@ impl ToCqlData for bool {
     }
 }
 
+impl<T: ToCqlData> ToCqlData for Vec<T> {
+    fn to_cql(self) -> CqlType {
+        CqlType::List(self.into_iter().map(ToCqlData::to_cql).collect())
+    }
+}
+
 impl ToCqlData for time::OffsetDateTime {
     fn to_cql(self) -> CqlType {
         CqlType::Timestamp(self)
@@ -62,6 +70,10 @@ impl FromCqlData for time::OffsetDateTime {
     fn from_cql(result: &CqlType) -> Result<Self, Self::Error> {
         match result {
             CqlType::Timestamp(timestamp) => Ok(*timestamp),
+            // milliseconds since epoch, as stargate returns timestamps
+            CqlType::NumInt(millis) => {
+                Self::from_unix_timestamp_nanos(i128::from(*millis) * 1_000_000).map_err(|_| ())
+            }
             _ => Err(()),
         }
     }
@@ -72,6 +84,17 @@ pub trait FromCqlData: Sized {
     fn from_cql(result: &CqlType) -> Result<Self, Self::Error>;
 }
 
+// null cells are read as `None`
+impl<T: FromCqlData> FromCqlData for Option<T> {
+    type Error = T::Error;
+    fn from_cql(result: &CqlType) -> Result<Self, Self::Error> {
+        match result {
+            CqlType::Null => Ok(None),
+            other => T::from_cql(other).map(Some),
+        }
+    }
+}
+
 impl FromCqlData for String {
     type Error = ();
     fn from_cql(result: &CqlType) -> Result<Self, Self::Error> {
@@ -111,12 +134,30 @@ impl FromCqlData for f64 {
         }
     }
 }
-// consistency types
+// consistency types, `Serial` and `LocalSerial` are only valid for reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Consistency {
+    Any,
     One,
     Two,
+    Three,
+    Quorum,
+    All,
+    LocalQuorum,
+    EachQuorum,
+    LocalOne,
+    Serial,
+    LocalSerial,
+}
+
+// consistency of the paxos phase of lightweight transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerialConsistency {
+    Serial,
+    LocalSerial,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Status {
     Ok,
     NotOk,
@@ -150,6 +191,7 @@ impl FromCqlData for Status {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Uuid(pub i64);
 
 impl ToCqlData for Uuid {
@@ -171,6 +213,7 @@ Defining from and to conversions for store types and cql_type
 */
 use scylla::deserialize::DeserializeValue;
 use scylla::frame::response::result::ColumnType;
+use scylla::serialize::value::SerializeValue;
 use stargate_grpc::{
     proto::{value::Inner, ColumnSpec},
     ResultSet, Row, Value,
@@ -178,18 +221,23 @@ use stargate_grpc::{
 
 // astra store type conversions
 pub trait IntoValue {
-    fn into(self: Box<Self>) -> Value;
+    fn try_into_value(self: Box<Self>) -> Result<Value, UnboundRow>;
 }
 
 impl<T> IntoValue for T
 where
-    T: Into<Value>,
+    Value: TryFrom<T, Error = UnboundRow>,
 {
-    fn into(self: Box<Self>) -> Value {
-        Into::into(*self)
+    fn try_into_value(self: Box<Self>) -> Result<Value, UnboundRow> {
+        Value::try_from(*self)
     }
 }
 
+// rows are only read from results, they can't be bound as a value
+#[derive(Debug, thiserror::Error)]
+#[error("a row can't be bound as a value")]
+pub struct UnboundRow;
+
 enum AstraResultIter {
     //curr: Option<CqlMap>,
     Rows(Vec<Row>, Vec<ColumnSpec>),
@@ -197,7 +245,9 @@ enum AstraResultIter {
 }
 
 impl AstraResultIter {
-    fn new(column: Vec<ColumnSpec>, rows: Vec<Row>) -> Self {
+    fn new(column: Vec<ColumnSpec>, mut rows: Vec<Row>) -> Self {
+        // rows are popped from the back, keep them in the order of the result set
+        rows.reverse();
         Self::Rows(rows, column)
     }
 
@@ -235,31 +285,50 @@ impl ToCqlRow for ResultSet {
 
 impl ToCqlData for Value {
     fn to_cql(self) -> CqlType {
-        if let None = self.inner {
+        let Some(inner) = self.inner else {
             return CqlType::Null;
-        }
+        };
 
-        match self.inner.unwrap() {
+        match inner {
             Inner::Int(i) => CqlType::NumInt(i),
             Inner::Double(f) => CqlType::NumFloat(f),
-            Inner::Float(f) => CqlType::NumFloat(f as f64),
+            Inner::Float(f) => CqlType::NumFloat(f64::from(f)),
             Inner::Boolean(b) => CqlType::Bool(b),
             Inner::String(s) => CqlType::Str(s),
             Inner::Bytes(b) => CqlType::Bytes(b),
+            // lists and sets
+            Inner::Collection(c) => {
+                CqlType::List(c.elements.into_iter().map(ToCqlData::to_cql).collect())
+            }
             Inner::Null(_) => CqlType::Null,
+            #[allow(clippy::unimplemented)]
             _ => unimplemented!("this type is not implemented"),
         }
     }
 }
 
-//TODO : implement for all types
-impl Into<Value> for CqlType {
-    fn into(self) -> Value {
-        match self {
-            //CqlType::NumInt(i) => Value::int(i),
-            CqlType::Str(s) => Value::string(s),
-            _ => unimplemented!("this type is not implemented"),
-        }
+impl TryFrom<CqlType> for Value {
+    type Error = UnboundRow;
+
+    fn try_from(value: CqlType) -> Result<Self, Self::Error> {
+        Ok(match value {
+            CqlType::NumInt(i) => Self::int(i),
+            CqlType::NumFloat(f) => Self::double(f),
+            CqlType::Bool(b) => Self::boolean(b),
+            CqlType::Str(s) => Self::string(s),
+            CqlType::Bytes(b) => Self::bytes(b),
+            // stargate takes timestamps as milliseconds since epoch
+            CqlType::Timestamp(t) => {
+                Self::int(i64::try_from(t.unix_timestamp_nanos() / 1_000_000).unwrap_or(i64::MAX))
+            }
+            CqlType::List(l) => Self::list(
+                l.into_iter()
+                    .map(Self::try_from)
+                    .collect::<Result<Vec<Self>, UnboundRow>>()?,
+            ),
+            CqlType::Null => Self::null(),
+            CqlType::Row(_) => return Err(UnboundRow),
+        })
     }
 }
 
@@ -275,41 +344,53 @@ impl std::fmt::Display for UnknownType {
 // scylla-cql type conversions
 impl<'frame, 'meta> DeserializeValue<'frame, 'meta> for CqlType {
     fn deserialize(
-        typ: &'meta scylla::frame::response::result::ColumnType<'meta>,
+        typ: &'meta ColumnType<'meta>,
         v: Option<scylla::deserialize::FrameSlice<'frame>>,
     ) -> Result<Self, scylla::deserialize::DeserializationError> {
+        // null cells are surfaced as CqlType::Null, models decide how to treat them
+        if v.is_none() {
+            return Ok(Self::Null);
+        }
         match typ {
-            ColumnType::Boolean => Ok(CqlType::Bool(bool::deserialize(typ, v)?)),
-            ColumnType::Decimal => Ok(CqlType::NumFloat(f64::deserialize(typ, v)?)),
-            ColumnType::Double => Ok(CqlType::NumFloat(f64::deserialize(typ, v)?)),
-            ColumnType::Float => Ok(CqlType::NumFloat(f64::deserialize(typ, v)?)),
-            ColumnType::Int => Ok(CqlType::NumInt(i64::deserialize(typ, v)?)),
-            ColumnType::BigInt => Ok(CqlType::NumInt(i64::deserialize(typ, v)?)),
-            ColumnType::Text => Ok(CqlType::Str(String::deserialize(typ, v)?)),
-            ColumnType::Timestamp => Ok(CqlType::Timestamp(time::OffsetDateTime::deserialize(
-                typ, v,
-            )?)),
+            ColumnType::Boolean => Ok(Self::Bool(bool::deserialize(typ, v)?)),
+            ColumnType::Decimal => Ok(Self::NumFloat(f64::deserialize(typ, v)?)),
+            ColumnType::Double => Ok(Self::NumFloat(f64::deserialize(typ, v)?)),
+            ColumnType::Float => Ok(Self::NumFloat(f32::deserialize(typ, v)?.into())),
+            ColumnType::TinyInt => Ok(Self::NumInt(i8::deserialize(typ, v)?.into())),
+            ColumnType::SmallInt => Ok(Self::NumInt(i16::deserialize(typ, v)?.into())),
+            ColumnType::Int => Ok(Self::NumInt(i32::deserialize(typ, v)?.into())),
+            ColumnType::BigInt => Ok(Self::NumInt(i64::deserialize(typ, v)?)),
+            ColumnType::Text => Ok(Self::Str(String::deserialize(typ, v)?)),
+            ColumnType::Timestamp => {
+                Ok(Self::Timestamp(time::OffsetDateTime::deserialize(typ, v)?))
+            }
+            // elements are read against the element type of the list or set
+            ColumnType::List(_) | ColumnType::Set(_) => {
+                Ok(Self::List(Vec::<Self>::deserialize(typ, v)?))
+            }
             _other => Err(scylla::deserialize::DeserializationError::new(UnknownType)),
         }
     }
 
-    fn type_check(
-        typ: &scylla::frame::response::result::ColumnType,
-    ) -> Result<(), scylla::deserialize::TypeCheckError> {
+    fn type_check(typ: &ColumnType) -> Result<(), scylla::deserialize::TypeCheckError> {
         match typ {
             ColumnType::Boolean
             | ColumnType::Decimal
             | ColumnType::Double
             | ColumnType::Float
+            | ColumnType::TinyInt
+            | ColumnType::SmallInt
             | ColumnType::Int
             | ColumnType::BigInt
-            | ColumnType::Text => Ok(()),
+            | ColumnType::Text
+            | ColumnType::Timestamp => Ok(()),
+            ColumnType::List(element) | ColumnType::Set(element) => Self::type_check(element),
             _other => Err(scylla::deserialize::TypeCheckError::new(UnknownType)),
         }
     }
 }
 
-impl scylla::serialize::value::SerializeValue for CqlType {
+impl SerializeValue for CqlType {
     fn serialize<'b>(
         &self,
         typ: &ColumnType,
@@ -319,14 +400,135 @@ impl scylla::serialize::value::SerializeValue for CqlType {
         scylla::serialize::SerializationError,
     > {
         match self {
-            CqlType::Str(s) => s.serialize(&ColumnType::Text, writer),
-            CqlType::Bool(s) => s.serialize(&ColumnType::Boolean, writer),
-            CqlType::Row(s) => Err(scylla::serialize::SerializationError::new(UnknownType)), //s.serialize(ColumnType::Map(Box<Text>,Box<CqlType>), writer),
-            CqlType::NumInt(s) => s.serialize(&ColumnType::BigInt, writer),
-            CqlType::NumFloat(s) => s.serialize(&ColumnType::Double, writer),
-            CqlType::Timestamp(s) => s.serialize(&ColumnType::Timestamp, writer),
-            CqlType::Bytes(s) => s.serialize(&ColumnType::Text, writer),
-            CqlType::Null => Err(scylla::serialize::SerializationError::new(UnknownType)),
+            Self::Str(s) => s.serialize(&ColumnType::Text, writer),
+            Self::Bool(s) => s.serialize(&ColumnType::Boolean, writer),
+            Self::Row(_) => Err(scylla::serialize::SerializationError::new(UnknownType)), //s.serialize(ColumnType::Map(Box<Text>,Box<CqlType>), writer),
+            // ints are kept as i64, narrowed to the column type when it's smaller
+            Self::NumInt(s) => match typ {
+                ColumnType::Int => i32::try_from(*s)
+                    .map_err(|_| scylla::serialize::SerializationError::new(UnknownType))?
+                    .serialize(&ColumnType::Int, writer),
+                ColumnType::SmallInt => i16::try_from(*s)
+                    .map_err(|_| scylla::serialize::SerializationError::new(UnknownType))?
+                    .serialize(&ColumnType::SmallInt, writer),
+                ColumnType::TinyInt => i8::try_from(*s)
+                    .map_err(|_| scylla::serialize::SerializationError::new(UnknownType))?
+                    .serialize(&ColumnType::TinyInt, writer),
+                _ => s.serialize(&ColumnType::BigInt, writer),
+            },
+            Self::NumFloat(s) => s.serialize(&ColumnType::Double, writer),
+            Self::Timestamp(s) => s.serialize(&ColumnType::Timestamp, writer),
+            Self::Bytes(s) => s.serialize(&ColumnType::Text, writer),
+            // elements are serialized against the element type of the list or set
+            Self::List(s) => s.serialize(typ, writer),
+            Self::Null => Err(scylla::serialize::SerializationError::new(UnknownType)),
         }
     }
 }
+
+// model fields are bound and read by the generated rows without a `CqlType`
+impl SerializeValue for Status {
+    fn serialize<'b>(
+        &self,
+        typ: &ColumnType,
+        writer: scylla::serialize::writers::CellWriter<'b>,
+    ) -> Result<
+        scylla::serialize::writers::WrittenCellProof<'b>,
+        scylla::serialize::SerializationError,
+    > {
+        self.to_string().serialize(typ, writer)
+    }
+}
+
+impl<'frame, 'meta> DeserializeValue<'frame, 'meta> for Status {
+    fn deserialize(
+        typ: &'meta ColumnType<'meta>,
+        v: Option<scylla::deserialize::FrameSlice<'frame>>,
+    ) -> Result<Self, scylla::deserialize::DeserializationError> {
+        match <&str>::deserialize(typ, v)? {
+            "Ok" => Ok(Self::Ok),
+            "NotOk" => Ok(Self::NotOk),
+            _ => Err(scylla::deserialize::DeserializationError::new(UnknownType)),
+        }
+    }
+
+    fn type_check(typ: &ColumnType) -> Result<(), scylla::deserialize::TypeCheckError> {
+        <&str>::type_check(typ)
+    }
+}
+
+impl SerializeValue for Uuid {
+    fn serialize<'b>(
+        &self,
+        typ: &ColumnType,
+        writer: scylla::serialize::writers::CellWriter<'b>,
+    ) -> Result<
+        scylla::serialize::writers::WrittenCellProof<'b>,
+        scylla::serialize::SerializationError,
+    > {
+        self.0.serialize(typ, writer)
+    }
+}
+
+impl<'frame, 'meta> DeserializeValue<'frame, 'meta> for Uuid {
+    fn deserialize(
+        typ: &'meta ColumnType<'meta>,
+        v: Option<scylla::deserialize::FrameSlice<'frame>>,
+    ) -> Result<Self, scylla::deserialize::DeserializationError> {
+        i64::deserialize(typ, v).map(Self)
+    }
+
+    fn type_check(typ: &ColumnType) -> Result<(), scylla::deserialize::TypeCheckError> {
+        i64::type_check(typ)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use scylla::{deserialize::FrameSlice, serialize::writers::CellWriter};
+
+    fn ints(list: CqlType) -> Result<Vec<i64>, &'static str> {
+        let CqlType::List(values) = list else {
+            return Err("not a list");
+        };
+        values
+            .iter()
+            .map(i64::from_cql)
+            .collect::<Result<Vec<i64>, ()>>()
+            .map_err(|()| "not an int")
+    }
+
+    #[test]
+    fn scylla_list_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let typ = ColumnType::List(Box::new(ColumnType::BigInt));
+        let list = CqlType::List(vec![CqlType::NumInt(1), CqlType::NumInt(2)]);
+
+        let mut cell = Vec::new();
+        SerializeValue::serialize(&list, &typ, CellWriter::new(&mut cell))?;
+        // the cell is prefixed with its length
+        let value = cell.get(4..).ok_or("cell without a value")?;
+        CqlType::type_check(&typ)?;
+        let read = CqlType::deserialize(&typ, Some(FrameSlice::new_borrowed(value)))?;
+
+        assert_eq!(ints(read)?, vec![1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn stargate_list_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let list = CqlType::List(vec![CqlType::NumInt(1), CqlType::NumInt(2)]);
+
+        let read = Value::try_from(list)?.to_cql();
+
+        assert_eq!(ints(read)?, vec![1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn rows_are_not_bound() {
+        let row = CqlType::List(vec![CqlType::Row(CqlMap::new())]);
+        assert!(Value::try_from(row).is_err());
+    }
+}
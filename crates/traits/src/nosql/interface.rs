@@ -1,9 +1,28 @@
-use crate::data_types::types::{FromCqlData, ToCqlData};
+use crate::data_types::types::{Consistency, FromCqlData, ToCqlData};
+use crate::nosql::schema::{TableSchema, UnknownCqlType};
+use crate::query::client::FilterBy;
 
 pub trait NoSql: FromCqlData + ToCqlData {
+    // `{Model}Key` with the partition key columns
+    type Key: FromCqlData + Into<FilterBy<Self>>;
+    // `{Model}PrimaryKey` with the partition and clustering key columns
+    type PrimaryKey: Into<FilterBy<Self>>;
+
     fn table_name() -> &'static str;
     fn keyspace() -> &'static str;
     fn insert_statement() -> &'static str;
+    // fails for models with a column whose cql type can't be inferred
+    fn create_table_statement() -> Result<&'static str, UnknownCqlType>;
+    // default_time_to_live of the table, from `default_ttl` on the model, applied by
+    // cassandra to writes that don't set a ttl
+    fn default_ttl() -> Option<std::time::Duration>;
+    fn schema() -> &'static TableSchema;
+    // consistency of queries that don't set one, from `consistency` on the model
+    fn consistency() -> Option<Consistency> {
+        None
+    }
+    fn key(&self) -> Self::Key;
+    fn primary_key(&self) -> Self::PrimaryKey;
 }
 
 #[async_trait::async_trait]
@@ -17,5 +36,9 @@ pub trait CqlStore: Sized {
         self,
         statement: Self::Statement,
     ) -> Result<Self::Output, Self::StoreError>;
-    async fn into_query(&self, statement: Self::Statement) -> Self::Query;
+    #[allow(clippy::wrong_self_convention)]
+    async fn into_query(
+        &self,
+        statement: Self::Statement,
+    ) -> Result<Self::Query, Self::StoreError>;
 }
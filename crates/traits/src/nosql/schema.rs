@@ -0,0 +1,75 @@
+// table metadata generated by `sin::nosql` for every model
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    PartitionKey,
+    ClusteringKey,
+    Static,
+    Regular,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClusteringOrder {
+    Asc,
+    Desc,
+}
+
+// a column without a `#[sin(cql_type = "..")]` whose type can't be inferred
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("can't infer the cql type of `{column}`, use `#[sin(cql_type = \"..\")]`")]
+pub struct UnknownCqlType {
+    pub column: &'static str,
+}
+
+#[derive(Debug)]
+pub struct ColumnSchema {
+    pub name: &'static str,
+    // `None` for fields without a `#[sin(cql_type = "..")]` whose type can't be inferred
+    pub cql_type: Option<&'static str>,
+    pub kind: ColumnKind,
+    // position within the partition or clustering key
+    pub position: Option<usize>,
+    pub order: Option<ClusteringOrder>,
+}
+
+impl ColumnSchema {
+    pub fn is_primary_key(&self) -> bool {
+        matches!(
+            self.kind,
+            ColumnKind::PartitionKey | ColumnKind::ClusteringKey
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct TableSchema {
+    pub keyspace: &'static str,
+    pub table: &'static str,
+    pub columns: &'static [ColumnSchema],
+}
+
+impl TableSchema {
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    // partition keys in key order
+    pub fn partition_keys(&self) -> Vec<&ColumnSchema> {
+        self.keys_of(ColumnKind::PartitionKey)
+    }
+
+    // clustering keys in key order
+    pub fn clustering_keys(&self) -> Vec<&ColumnSchema> {
+        self.keys_of(ColumnKind::ClusteringKey)
+    }
+
+    fn keys_of(&self, kind: ColumnKind) -> Vec<&ColumnSchema> {
+        let mut keys = self
+            .columns
+            .iter()
+            .filter(|column| column.kind == kind)
+            .collect::<Vec<&ColumnSchema>>();
+        keys.sort_by_key(|column| column.position);
+        keys
+    }
+}
@@ -1,27 +1,184 @@
 use crate::{
-    data_types::types::{CqlMap, CqlMapWithQuery, ToCqlRow},
-    nosql::interface::NoSql,
-    query::query::{Create, FindAll, FindOne, Update},
+    data_types::types::{
+        Consistency, CqlMap, CqlMapWithQuery, CqlType, FromCqlData, QueryString, SerialConsistency,
+        ToCqlRow,
+    },
+    nosql::{interface::NoSql, schema::ClusteringOrder},
+    query::{
+        column::{Clustering, Col, Combine, Filtering, NonKey, Regular, Restricted, Restriction},
+        execution::{Execution, RetryPolicy},
+        page::{Cursor, FindPage},
+        scan::{Checkpoint, Scan, TokenRange},
+        query::{
+            Aggregate, Batch, BatchType, Batchable, Create, Delete, DistinctKeys, FindAll, FindOne,
+            GetMany, Lwt, QueryError, Update,
+        },
+    },
 };
-use std::marker::PhantomData;
+use std::{borrow::Cow, marker::PhantomData, num::NonZeroU32, time::Duration};
 
-pub struct FilterBy<T> {
+// statements of a key prefix filter, generated by the `nosql` macro so builders
+// don't have to format them
+#[derive(Clone, Copy)]
+struct KeyStatements {
+    select: &'static str,
+    delete: &'static str,
+    // SET clause of the model's update struct with the statement using it
+    update: Option<(&'static str, &'static str)>,
+}
+
+// `{column} {operator} ?` of a where clause, bound by column name, or
+// `{column} {operator} :{bind}` with a named marker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relation {
+    column: &'static str,
+    operator: &'static str,
+    bind: Option<Cow<'static, str>>,
+}
+
+impl Relation {
+    pub const fn eq(column: &'static str) -> Self {
+        Self {
+            column,
+            operator: "=",
+            bind: None,
+        }
+    }
+
+    pub fn named(
+        column: &'static str,
+        operator: &'static str,
+        bind: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            column,
+            operator,
+            bind: Some(bind.into()),
+        }
+    }
+
+    // key of the value of the relation in the binds of the filter
+    fn bind(&self) -> &str {
+        self.bind.as_deref().unwrap_or(self.column)
+    }
+}
+
+impl std::fmt::Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.bind {
+            Some(bind) => write!(f, "{} {} :{}", self.column, self.operator, bind),
+            None => write!(f, "{} {} ?", self.column, self.operator),
+        }
+    }
+}
+
+// where clause of a query, filters restricting anything other than
+// primary key columns are marked as `NonKey` or `Filtering`
+pub struct FilterBy<T, R: Restriction = Restricted> {
     filter: CqlMap, // TODO: should be impl IntoExpression
-    query_string: &'static str,
-    _model: PhantomData<fn() -> T>,
+    // generated key filters borrow their relations from a constant
+    relations: Cow<'static, [Relation]>,
+    statements: Option<KeyStatements>,
+    _model: PhantomData<fn() -> (T, R)>,
 }
 
-impl<T> FilterBy<T> {
-    pub fn new(filter: CqlMap, query_string: &'static str) -> Self {
+impl<T, R: Restriction> FilterBy<T, R> {
+    pub fn new(filter: CqlMap, relations: impl Into<Cow<'static, [Relation]>>) -> Self {
         Self {
             filter,
-            query_string,
+            relations: relations.into(),
+            statements: None,
             _model: PhantomData,
         }
     }
+
+    // `SELECT * ..` and `DELETE ..` statements with this filter as their where clause,
+    // used by builders that add nothing else to the query
+    pub fn with_statements(mut self, select: &'static str, delete: &'static str) -> Self {
+        self.statements = Some(KeyStatements {
+            select,
+            delete,
+            update: None,
+        });
+        self
+    }
+
+    // `UPDATE .. SET {set_clause} ..` with this filter as its where clause
+    pub fn with_update_statement(mut self, set_clause: &'static str, update: &'static str) -> Self {
+        if let Some(statements) = self.statements.as_mut() {
+            statements.update = Some((set_clause, update));
+        }
+        self
+    }
+
+    // relations joined with AND, only formatted for queries that don't use
+    // the generated statements
+    pub fn query_string(&self) -> String {
+        self.relations
+            .iter()
+            .map(Relation::to_string)
+            .collect::<Vec<String>>()
+            .join(" AND ")
+    }
+
+    // binds of `other` already used by this filter are renamed to `{bind}_{n}`, e.g.
+    // `amount > :amount_gt AND amount < :amount_gt_1`, so no value is overwritten
+    pub fn and<O: Restriction>(mut self, other: FilterBy<T, O>) -> FilterBy<T, R::Output>
+    where
+        R: Combine<O>,
+    {
+        let mut other_filter = other.filter;
+        let relations = self.relations.to_mut();
+        for relation in other.relations.iter() {
+            let Some(value) = other_filter.remove(relation.bind()) else {
+                relations.push(relation.clone());
+                continue;
+            };
+            if !self.filter.contains_key(relation.bind()) {
+                self.filter.insert(relation.bind().to_string(), value);
+                relations.push(relation.clone());
+                continue;
+            }
+            let bind = (1..)
+                .map(|n| format!("{}_{}", relation.bind(), n))
+                .find(|bind| !self.filter.contains_key(bind) && !other_filter.contains_key(bind))
+                .unwrap_or_default();
+            relations.push(Relation::named(
+                relation.column,
+                relation.operator,
+                bind.clone(),
+            ));
+            self.filter.insert(bind, value);
+        }
+        FilterBy::new(self.filter, self.relations)
+    }
+
+    // turns the filter into an IF clause, binds are renamed to named markers
+    // so they can't clash with the binds of the SET and WHERE clauses
+    fn into_condition(self) -> (CqlMap, String) {
+        let binds = self
+            .filter
+            .into_iter()
+            .map(|(key, value)| (format!("if_{}", key), value))
+            .collect();
+        let conditions = self
+            .relations
+            .iter()
+            .map(|relation| {
+                Relation::named(
+                    relation.column,
+                    relation.operator,
+                    format!("if_{}", relation.bind()),
+                )
+                .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join(" AND ");
+        (binds, format!("IF {}", conditions))
+    }
 }
 
-trait State {}
+pub trait State {}
 
 pub struct Limit;
 impl State for Limit {}
@@ -32,25 +189,172 @@ impl State for Init {}
 pub struct Ready;
 impl State for Ready {}
 
+pub struct Ordered;
+impl State for Ordered {}
+
+// conditional writes carry their IF clause
+pub struct Conditional(Condition);
+impl State for Conditional {}
+
+// IF clause of a lightweight transaction with its binds
+struct Condition {
+    binds: CqlMap,
+    clause: String,
+    serial_consistency: Option<SerialConsistency>,
+}
+
+impl Condition {
+    fn new(binds: CqlMap, clause: String) -> Self {
+        Self {
+            binds,
+            clause,
+            serial_consistency: None,
+        }
+    }
+
+    fn not_exists() -> Self {
+        Self::new(CqlMap::new(), "IF NOT EXISTS".to_string())
+    }
+
+    fn exists() -> Self {
+        Self::new(CqlMap::new(), "IF EXISTS".to_string())
+    }
+
+    fn build<T: NoSql>(self, mut binds: CqlMap, query_string: &str) -> Lwt<T> {
+        binds.extend(self.binds);
+        Lwt::<T>::create_query(
+            binds,
+            format!("{} {}", query_string, self.clause),
+            self.serial_consistency,
+        )
+    }
+}
+
+// USING clause of writes, binds use the names cassandra gives to these markers,
+// the `default_ttl` of a model is left to the table's default_time_to_live
+#[derive(Default)]
+struct Using {
+    ttl: Option<Duration>,
+    timestamp: Option<i64>,
+}
+
+impl Using {
+    fn into_clause(self, binds: &mut CqlMap) -> Option<String> {
+        let mut options = Vec::with_capacity(2);
+        if let Some(ttl) = self.ttl {
+            options.push("TTL ?");
+            binds.insert("[ttl]".to_string(), CqlType::NumInt(ttl_secs(ttl)));
+        }
+        if let Some(timestamp) = self.timestamp {
+            options.push("TIMESTAMP ?");
+            binds.insert("[timestamp]".to_string(), CqlType::NumInt(timestamp));
+        }
+        if options.is_empty() {
+            return None;
+        }
+        Some(format!("USING {}", options.join(" AND ")))
+    }
+}
+
+// ttls are sent in whole seconds, rounded up so a sub-second ttl doesn't expire
+// right away, ttls out of range are rejected by cassandra
+fn ttl_secs(ttl: Duration) -> i64 {
+    let secs = match ttl.subsec_nanos() {
+        0 => ttl.as_secs(),
+        _ => ttl.as_secs().saturating_add(1),
+    };
+    i64::try_from(secs).unwrap_or(i64::MAX)
+}
+
 // for inserts impl by NoSql models
 pub trait Insertable: NoSql {
-    fn create(self) -> InsertBuilder<Self> {
+    fn create(self) -> InsertBuilder<Self, Init> {
         InsertBuilder {
             model: self,
             prepared_statement: Some(Self::insert_statement()),
+            using: Using::default(),
+            consistency: Self::consistency(),
+            execution: Execution::default(),
+            state: Init,
         }
     }
 }
 
-pub struct InsertBuilder<T: NoSql> {
+pub struct InsertBuilder<T: NoSql, S: State> {
     model: T,
     prepared_statement: Option<&'static str>,
-    // consistency :  add Consistency
+    using: Using,
+    consistency: Option<Consistency>,
+    execution: Execution,
+    state: S,
+}
+
+impl<T: NoSql, S: State> InsertBuilder<T, S> {
+    // overrides the consistency of the model
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    // ttl of the written cells, writes without one expire after the
+    // default_time_to_live of the table
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.using.ttl = Some(ttl);
+        self
+    }
+
+    // write time in microseconds since epoch
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.using.timestamp = Some(timestamp);
+        self
+    }
 }
 
-impl<T: NoSql> InsertBuilder<T> {
+impl<T: NoSql> InsertBuilder<T, Init> {
     pub fn build(self) -> Create<T> {
-        Create::<T>::create_query(self.model)
+        let mut binds = CqlMap::new();
+        let create = match self.using.into_clause(&mut binds) {
+            Some(using) => Create::<T>::create_query(self.model)
+                .with_options(format!("{} {}", T::insert_statement(), using), binds),
+            None => Create::<T>::create_query(self.model),
+        };
+        create
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+    }
+
+    pub fn if_not_exists(self) -> InsertBuilder<T, Conditional> {
+        InsertBuilder {
+            model: self.model,
+            prepared_statement: self.prepared_statement,
+            using: self.using,
+            consistency: self.consistency,
+            execution: self.execution,
+            state: Conditional(Condition::not_exists()),
+        }
+    }
+}
+
+impl<T: NoSql> InsertBuilder<T, Conditional> {
+    pub fn serial_consistency(mut self, serial_consistency: SerialConsistency) -> Self {
+        self.state.0.serial_consistency = Some(serial_consistency);
+        self
+    }
+
+    pub fn build(self) -> Result<Lwt<T>, QueryError> {
+        let Conditional(condition) = self.state;
+        let CqlType::Row(binds) = self.model.to_cql() else {
+            return Err(QueryError::Encode);
+        };
+        let mut lwt = condition
+            .build(binds, T::insert_statement())
+            .with_consistency(self.consistency)
+            .with_execution(self.execution);
+        // USING comes after IF NOT EXISTS in inserts
+        if let Some(using) = self.using.into_clause(&mut lwt.binds) {
+            lwt.query = format!("{} {}", lwt.query, using).into();
+        }
+        Ok(lwt)
     }
 }
 
@@ -62,158 +366,923 @@ pub trait Selectable: NoSql {
     fn select_all() -> SelectAllBuilder<Self, Init> {
         SelectAllBuilder::<Self, Init>::default()
     }
+    fn scan() -> ScanBuilder<Self> {
+        ScanBuilder::<Self>::default()
+    }
+    fn get_many(keys: impl IntoIterator<Item = Self::PrimaryKey>) -> GetManyBuilder<Self> {
+        GetManyBuilder::<Self>::new(keys.into_iter().collect())
+    }
+    fn select_distinct_keys() -> DistinctKeys<Self> {
+        let partition_keys = Self::schema()
+            .partition_keys()
+            .iter()
+            .map(|column| column.name)
+            .collect::<Vec<&str>>()
+            .join(", ");
+        DistinctKeys::<Self>::create_query(format!(
+            "SELECT DISTINCT {} FROM {}.{}",
+            partition_keys,
+            Self::keyspace(),
+            Self::table_name()
+        ))
+    }
+    fn count() -> AggregateBuilder<Self, i64> {
+        AggregateBuilder::<Self, i64>::new("COUNT(*)".to_string())
+    }
+    // `None` when no row matches
+    fn min<V: FromCqlData, K>(column: Col<Self, V, K>) -> AggregateBuilder<Self, Option<V>> {
+        AggregateBuilder::<Self, Option<V>>::new(format!("MIN({})", column.name()))
+    }
+    fn max<V: FromCqlData, K>(column: Col<Self, V, K>) -> AggregateBuilder<Self, Option<V>> {
+        AggregateBuilder::<Self, Option<V>>::new(format!("MAX({})", column.name()))
+    }
+    // zero when no row matches
+    fn sum<V: FromCqlData, K>(column: Col<Self, V, K>) -> AggregateBuilder<Self, V> {
+        AggregateBuilder::<Self, V>::new(format!("SUM({})", column.name()))
+    }
+    fn avg<V: FromCqlData, K>(column: Col<Self, V, K>) -> AggregateBuilder<Self, V> {
+        AggregateBuilder::<Self, V>::new(format!("AVG({})", column.name()))
+    }
 }
 
 pub struct SelectBuilder<T: NoSql, S: State> {
     wh_clause: Option<FilterBy<T>>,
+    consistency: Option<Consistency>,
+    execution: Execution,
     state: S,
     _model: PhantomData<T>,
 }
 
-impl<T: NoSql> SelectBuilder<T, Init> {
-    pub fn default() -> Self {
+impl<T: NoSql, S: State> SelectBuilder<T, S> {
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+}
+
+impl<T: NoSql> Default for SelectBuilder<T, Init> {
+    fn default() -> Self {
         Self {
             wh_clause: None,
+            consistency: T::consistency(),
+            execution: Execution::read(),
             state: Init,
             _model: PhantomData,
         }
     }
+}
 
+impl<T: NoSql> SelectBuilder<T, Init> {
     pub fn filter_by(self, filter: FilterBy<T>) -> SelectBuilder<T, Ready> {
         SelectBuilder {
             wh_clause: Some(filter),
+            consistency: self.consistency,
+            execution: self.execution,
             state: Ready,
             _model: self._model,
         }
     }
 }
 impl<T: NoSql> SelectBuilder<T, Ready> {
+    // the Ready state is only reached through filter_by
+    #[allow(clippy::expect_used, clippy::missing_panics_doc)]
     pub fn build(self) -> FindOne<T> {
         let filter = self.wh_clause.expect("filter not found");
-        let query_string = format!(
-            "SELECT * FROM {}.{} WHERE {}",
-            T::keyspace(),
-            T::table_name(),
-            filter.query_string
-        );
+        let query_string: QueryString = match filter.statements {
+            Some(statements) => statements.select.into(),
+            None => format!(
+                "SELECT * FROM {}.{} WHERE {}",
+                T::keyspace(),
+                T::table_name(),
+                filter.query_string()
+            )
+            .into(),
+        };
         FindOne::<T>::create_query(filter.filter, query_string)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
     }
 }
 
-pub struct SelectAllBuilder<T: NoSql, S: State> {
-    wh_clause: Option<FilterBy<T>>,
+// `R` is the restriction of the filters the builder takes, `Filtering` builders
+// come from `allow_filtering` and add ALLOW FILTERING to the query
+pub struct SelectAllBuilder<T: NoSql, S: State, R: Restriction = Restricted> {
+    wh_clause: Option<FilterBy<T, R>>,
     state: S,
+    order_by: Vec<(&'static str, ClusteringOrder)>,
+    per_partition_limit: Option<u64>,
     limit: Option<u64>,
+    page_size: Option<NonZeroU32>,
+    consistency: Option<Consistency>,
+    execution: Execution,
     _model: PhantomData<T>,
 }
 
-impl<T: NoSql> SelectAllBuilder<T, Init> {
-    pub fn default() -> Self {
-        Self {
-            wh_clause: None,
-            state: Init,
-            _model: PhantomData,
-            limit: None,
-        }
+// states of a select that has no filter yet
+pub trait Unfiltered: State {}
+impl Unfiltered for Init {}
+impl Unfiltered for Limit {}
+impl Unfiltered for Ordered {}
+
+// `Init` and `Ordered` selects can't be built, a select without a filter has to be
+// limited and ordering needs the partition to be restricted
+impl<T: NoSql, S: State, R: Restriction> SelectAllBuilder<T, S, R> {
+    // rows fetched per page when the query is streamed
+    pub fn page_size(mut self, page_size: NonZeroU32) -> Self {
+        self.page_size = Some(page_size);
+        self
     }
 
-    pub fn limit(self, limit: u64) -> SelectAllBuilder<T, Limit> {
+    pub fn per_partition_limit(mut self, per_partition_limit: u64) -> Self {
+        self.per_partition_limit = Some(per_partition_limit);
+        self
+    }
+
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    fn into_state<N: State, F: Restriction>(
+        self,
+        state: N,
+        wh_clause: Option<FilterBy<T, F>>,
+    ) -> SelectAllBuilder<T, N, F> {
         SelectAllBuilder {
-            wh_clause: self.wh_clause,
-            state: Limit,
-            limit: Some(limit),
-            _model: PhantomData,
+            wh_clause,
+            state,
+            order_by: self.order_by,
+            per_partition_limit: self.per_partition_limit,
+            limit: self.limit,
+            page_size: self.page_size,
+            consistency: self.consistency,
+            execution: self.execution,
+            _model: self._model,
         }
     }
 
+    fn with_limit<N: State>(mut self, limit: u64, state: N) -> SelectAllBuilder<T, N, R> {
+        self.limit = Some(limit);
+        let wh_clause = self.wh_clause.take();
+        self.into_state(state, wh_clause)
+    }
+
+    fn with_order<V, N: State>(
+        mut self,
+        column: Col<T, V, Clustering>,
+        order: ClusteringOrder,
+        state: N,
+    ) -> SelectAllBuilder<T, N, R> {
+        self.order_by.push((column.name(), order));
+        let wh_clause = self.wh_clause.take();
+        self.into_state(state, wh_clause)
+    }
+
+    // SELECT .. WHERE .. ORDER BY .. PER PARTITION LIMIT .. LIMIT .. ALLOW FILTERING
+    fn query_string(&self) -> QueryString {
+        let statements = self.wh_clause.as_ref().and_then(|clause| clause.statements);
+        if let Some(statements) = statements {
+            if self.order_by.is_empty()
+                && self.per_partition_limit.is_none()
+                && self.limit.is_none()
+                && !R::ALLOW_FILTERING
+            {
+                return statements.select.into();
+            }
+        }
+
+        let mut query_string = format!("SELECT * FROM {}.{}", T::keyspace(), T::table_name());
+        if let Some(clause) = &self.wh_clause {
+            query_string.push_str(&format!(" WHERE {}", clause.query_string()));
+        }
+        if !self.order_by.is_empty() {
+            let order_by = self
+                .order_by
+                .iter()
+                .map(|(column, order)| match order {
+                    ClusteringOrder::Asc => format!("{} ASC", column),
+                    ClusteringOrder::Desc => format!("{} DESC", column),
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            query_string.push_str(&format!(" ORDER BY {}", order_by));
+        }
+        if let Some(per_partition_limit) = self.per_partition_limit {
+            query_string.push_str(&format!(" PER PARTITION LIMIT {}", per_partition_limit));
+        }
+        if let Some(limit) = self.limit {
+            query_string.push_str(&format!(" LIMIT {}", limit));
+        }
+        if R::ALLOW_FILTERING {
+            query_string.push_str(" ALLOW FILTERING");
+        }
+        query_string.into()
+    }
+
+    fn into_query(self) -> FindAll<T> {
+        let query_string = self.query_string();
+        let filters = self.wh_clause.map(|clause| clause.filter);
+        FindAll::<T>::create_query(filters.unwrap_or_default(), query_string)
+            .with_page_size(self.page_size)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+    }
+}
+
+impl<T: NoSql, S: Unfiltered> SelectAllBuilder<T, S, Restricted> {
     pub fn filter_by(self, filter: FilterBy<T>) -> SelectAllBuilder<T, Ready> {
+        self.into_state(Ready, Some(filter))
+    }
+
+    // unlocks filters on any column, the query is sent with ALLOW FILTERING
+    pub fn allow_filtering(self) -> SelectAllBuilder<T, S, Filtering> {
         SelectAllBuilder {
-            wh_clause: Some(filter),
-            state: Ready,
+            wh_clause: None,
+            state: self.state,
+            order_by: self.order_by,
+            per_partition_limit: self.per_partition_limit,
+            limit: self.limit,
+            page_size: self.page_size,
+            consistency: self.consistency,
+            execution: self.execution,
             _model: self._model,
+        }
+    }
+}
+
+impl<T: NoSql, S: Unfiltered> SelectAllBuilder<T, S, Filtering> {
+    pub fn filter_by<F: Restriction>(
+        self,
+        filter: FilterBy<T, F>,
+    ) -> SelectAllBuilder<T, Ready, Filtering> {
+        let filter = FilterBy::new(filter.filter, filter.relations);
+        self.into_state(Ready, Some(filter))
+    }
+}
+
+impl<T: NoSql> Default for SelectAllBuilder<T, Init> {
+    fn default() -> Self {
+        Self {
+            wh_clause: None,
+            state: Init,
+            order_by: Vec::new(),
+            per_partition_limit: None,
             limit: None,
+            page_size: None,
+            consistency: T::consistency(),
+            execution: Execution::read(),
+            _model: PhantomData,
         }
     }
 }
-impl<T: NoSql> SelectAllBuilder<T, Ready> {
-    pub fn build(self) -> FindAll<T> {
-        let filter = self.wh_clause.expect("filter not found");
-        let query_string = format!(
-            "SELECT * FROM {}.{} WHERE {}",
-            T::keyspace(),
-            T::table_name(),
-            filter.query_string
-        );
-        FindAll::<T>::create_query(filter.filter, query_string)
+
+impl<T: NoSql, R: Restriction> SelectAllBuilder<T, Init, R> {
+    pub fn limit(self, limit: u64) -> SelectAllBuilder<T, Limit, R> {
+        self.with_limit(limit, Limit)
+    }
+
+    pub fn order_by<V>(
+        self,
+        column: Col<T, V, Clustering>,
+        order: ClusteringOrder,
+    ) -> SelectAllBuilder<T, Ordered, R> {
+        self.with_order(column, order, Ordered)
     }
 }
 
-impl<T: NoSql> SelectAllBuilder<T, Limit> {
+impl<T: NoSql, R: Restriction> SelectAllBuilder<T, Limit, R> {
+    pub fn limit(self, limit: u64) -> Self {
+        self.with_limit(limit, Limit)
+    }
+
+    pub fn order_by<V>(
+        self,
+        column: Col<T, V, Clustering>,
+        order: ClusteringOrder,
+    ) -> SelectAllBuilder<T, Ordered, R> {
+        self.with_order(column, order, Ordered)
+    }
+
     pub fn build(self) -> FindAll<T> {
-        let mut filters = None;
-        let mut query_string = format!("SELECT * FROM {}.{} ", T::keyspace(), T::table_name());
+        self.into_query()
+    }
 
-        if let Some(clause) = self.wh_clause {
-            filters = Some(clause.filter);
-            query_string.push_str(&format!("WHERE {}", clause.query_string));
-        }
+    pub fn page(self, page_size: NonZeroU32, cursor: Option<Cursor>) -> FindPage<T> {
+        FindPage::<T>::create_query(self.build(), page_size, cursor)
+    }
+}
 
-        let limit = self.limit.expect("Limit is expected");
+impl<T: NoSql, R: Restriction> SelectAllBuilder<T, Ordered, R> {
+    pub fn limit(self, limit: u64) -> Self {
+        self.with_limit(limit, Ordered)
+    }
 
-        query_string.push_str(&format!("LIMIT {}", limit));
+    pub fn order_by<V>(self, column: Col<T, V, Clustering>, order: ClusteringOrder) -> Self {
+        self.with_order(column, order, Ordered)
+    }
+}
 
-        FindAll::<T>::create_query(filters.unwrap_or_default(), query_string)
+impl<T: NoSql, R: Restriction> SelectAllBuilder<T, Ready, R> {
+    pub fn limit(self, limit: u64) -> Self {
+        self.with_limit(limit, Ready)
+    }
+
+    pub fn order_by<V>(self, column: Col<T, V, Clustering>, order: ClusteringOrder) -> Self {
+        self.with_order(column, order, Ready)
+    }
+
+    pub fn build(self) -> FindAll<T> {
+        self.into_query()
+    }
+
+    // a single page of `page_size` rows, starting where `cursor` left off
+    pub fn page(self, page_size: NonZeroU32, cursor: Option<Cursor>) -> FindPage<T> {
+        FindPage::<T>::create_query(self.build(), page_size, cursor)
     }
 }
 
 // for update impl by NoSql models
 pub trait Updateable: ToCqlRow<Output = CqlMapWithQuery> + Sized {
     type ParentModel: NoSql;
-    fn update(self) -> UpdateBuilder<Self::ParentModel, Init> {
-        let set_clause = self
-            .to_row_iter()
-            .next()
-            .expect("Update struct to cql row conversion failed");
-        UpdateBuilder::<Self::ParentModel, Init>::new(set_clause)
+    // `col = ?` for every field that is set
+    fn set_clause(&self) -> String;
+    fn update(self) -> UpdateBuilder<Self::ParentModel, Init, Self> {
+        UpdateBuilder::<Self::ParentModel, Init, Self>::new(self)
     }
 }
 
-pub struct UpdateBuilder<T: NoSql, S: State> {
-    set_clause: CqlMapWithQuery,
+// `U` is the update struct of the model, kept as is so clients can bind it column by column
+pub struct UpdateBuilder<T: NoSql, S: State, U> {
+    set: U,
+    set_clause: String,
     wh_clause: Option<FilterBy<T>>,
+    using: Using,
+    consistency: Option<Consistency>,
+    execution: Execution,
     state: S,
     _model: PhantomData<T>,
 }
 
-impl<T: NoSql> UpdateBuilder<T, Init> {
-    pub fn new(set_clause: CqlMapWithQuery) -> Self {
+impl<T: NoSql, S: State, U: Updateable> UpdateBuilder<T, S, U> {
+    // overrides the consistency of the model
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    // ttl of the written cells, writes without one expire after the
+    // default_time_to_live of the table
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.using.ttl = Some(ttl);
+        self
+    }
+
+    // write time in microseconds since epoch
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.using.timestamp = Some(timestamp);
+        self
+    }
+
+    // returns the where binds alongside the query, binds of the USING clause
+    // are part of the where binds, updates without any field set are rejected
+    #[allow(clippy::expect_used)]
+    fn into_query(self) -> Result<(CqlMap, U, QueryString, S), QueryError> {
+        if self.set_clause.is_empty() {
+            return Err(QueryError::EmptyUpdate);
+        }
+        let mut filter = self.wh_clause.expect("filter not found");
+        let using = self.using.into_clause(&mut filter.filter);
+        let update = filter.statements.and_then(|statements| statements.update);
+        let query_string: QueryString = match (using, update) {
+            (None, Some((set_clause, update))) if set_clause == self.set_clause => update.into(),
+            (using, _) => {
+                let table = match using {
+                    Some(using) => format!("{}.{} {}", T::keyspace(), T::table_name(), using),
+                    None => format!("{}.{}", T::keyspace(), T::table_name()),
+                };
+                format!(
+                    "UPDATE {} SET {} WHERE {}",
+                    table,
+                    self.set_clause,
+                    filter.query_string()
+                )
+                .into()
+            }
+        };
+        Ok((filter.filter, self.set, query_string, self.state))
+    }
+}
+
+impl<T: NoSql, U: Updateable> UpdateBuilder<T, Init, U> {
+    pub fn new(set: U) -> Self {
         Self {
-            set_clause,
+            set_clause: set.set_clause(),
+            set,
             wh_clause: None,
+            using: Using::default(),
+            consistency: T::consistency(),
+            execution: Execution::default(),
             state: Init,
             _model: PhantomData,
         }
     }
 
-    pub fn filter_by(self, filter: FilterBy<T>) -> UpdateBuilder<T, Ready> {
+    pub fn filter_by(self, filter: FilterBy<T>) -> UpdateBuilder<T, Ready, U> {
         UpdateBuilder {
+            set: self.set,
             set_clause: self.set_clause,
             wh_clause: Some(filter),
+            using: self.using,
+            consistency: self.consistency,
+            execution: self.execution,
             state: Ready,
             _model: self._model,
         }
     }
 }
-impl<T: NoSql> UpdateBuilder<T, Ready> {
-    pub fn build(self) -> Update<T> {
+impl<T: NoSql, U: Updateable> UpdateBuilder<T, Ready, U> {
+    pub fn build(self) -> Result<Update<T, U>, QueryError> {
+        let (consistency, execution) = (self.consistency, self.execution.clone());
+        let (where_binds, set, query_string, _) = self.into_query()?;
+        Ok(Update::<T, U>::create_query(where_binds, set, query_string)
+            .with_consistency(consistency)
+            .with_execution(execution))
+    }
+
+    pub fn if_exists(self) -> UpdateBuilder<T, Conditional, U> {
+        self.with_condition(Condition::exists())
+    }
+
+    // conditions can only be on regular columns, e.g. `.only_if(Payment::cols().amount.eq(10.0))`
+    pub fn only_if(self, condition: FilterBy<T, NonKey>) -> UpdateBuilder<T, Conditional, U> {
+        let (binds, clause) = condition.into_condition();
+        self.with_condition(Condition::new(binds, clause))
+    }
+
+    fn with_condition(self, condition: Condition) -> UpdateBuilder<T, Conditional, U> {
+        UpdateBuilder {
+            set: self.set,
+            set_clause: self.set_clause,
+            wh_clause: self.wh_clause,
+            using: self.using,
+            consistency: self.consistency,
+            execution: self.execution,
+            state: Conditional(condition),
+            _model: self._model,
+        }
+    }
+}
+
+impl<T: NoSql, U: Updateable> UpdateBuilder<T, Conditional, U> {
+    pub fn serial_consistency(mut self, serial_consistency: SerialConsistency) -> Self {
+        self.state.0.serial_consistency = Some(serial_consistency);
+        self
+    }
+
+    pub fn build(self) -> Result<Lwt<T>, QueryError> {
+        let (consistency, execution) = (self.consistency, self.execution.clone());
+        let (mut binds, set, query_string, Conditional(condition)) = self.into_query()?;
+        binds.extend(set.to_row_iter().flat_map(|(_, set_binds)| set_binds));
+        Ok(condition
+            .build(binds, &query_string)
+            .with_consistency(consistency)
+            .with_execution(execution))
+    }
+}
+
+// for deletes impl by NoSql models
+pub trait Deletable: NoSql {
+    fn delete() -> DeleteBuilder<Self, Init> {
+        DeleteBuilder::<Self, Init>::default()
+    }
+}
+
+pub struct DeleteBuilder<T: NoSql, S: State> {
+    // columns to delete, the whole row is deleted if empty
+    columns: Vec<&'static str>,
+    wh_clause: Option<FilterBy<T>>,
+    consistency: Option<Consistency>,
+    execution: Execution,
+    state: S,
+    _model: PhantomData<T>,
+}
+
+impl<T: NoSql, S: State> DeleteBuilder<T, S> {
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    #[allow(clippy::expect_used)]
+    fn into_query(self) -> (CqlMap, QueryString, S) {
         let filter = self.wh_clause.expect("filter not found");
+        if let (Some(statements), true) = (filter.statements, self.columns.is_empty()) {
+            return (filter.filter, statements.delete.into(), self.state);
+        }
+        let mut query_string = String::from("DELETE ");
+        if !self.columns.is_empty() {
+            query_string.push_str(&format!("{} ", self.columns.join(", ")));
+        }
+        query_string.push_str(&format!(
+            "FROM {}.{} WHERE {}",
+            T::keyspace(),
+            T::table_name(),
+            filter.query_string()
+        ));
+        (filter.filter, query_string.into(), self.state)
+    }
+}
+
+impl<T: NoSql> Default for DeleteBuilder<T, Init> {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            wh_clause: None,
+            consistency: T::consistency(),
+            execution: Execution::default(),
+            state: Init,
+            _model: PhantomData,
+        }
+    }
+}
+
+impl<T: NoSql> DeleteBuilder<T, Init> {
+    // primary key columns can't be deleted on their own
+    pub fn column<V>(mut self, column: Col<T, V, Regular>) -> Self {
+        self.columns.push(column.name());
+        self
+    }
+
+    pub fn filter_by(self, filter: FilterBy<T>) -> DeleteBuilder<T, Ready> {
+        DeleteBuilder {
+            columns: self.columns,
+            wh_clause: Some(filter),
+            consistency: self.consistency,
+            execution: self.execution,
+            state: Ready,
+            _model: self._model,
+        }
+    }
+}
+
+impl<T: NoSql> DeleteBuilder<T, Ready> {
+    pub fn build(self) -> Delete<T> {
+        let (consistency, execution) = (self.consistency, self.execution.clone());
+        let (binds, query_string, _) = self.into_query();
+        Delete::<T>::create_query(binds, query_string)
+            .with_consistency(consistency)
+            .with_execution(execution)
+    }
+
+    pub fn if_exists(self) -> DeleteBuilder<T, Conditional> {
+        self.with_condition(Condition::exists())
+    }
+
+    // conditions can only be on regular columns
+    pub fn only_if(self, condition: FilterBy<T, NonKey>) -> DeleteBuilder<T, Conditional> {
+        let (binds, clause) = condition.into_condition();
+        self.with_condition(Condition::new(binds, clause))
+    }
+
+    fn with_condition(self, condition: Condition) -> DeleteBuilder<T, Conditional> {
+        DeleteBuilder {
+            columns: self.columns,
+            wh_clause: self.wh_clause,
+            consistency: self.consistency,
+            execution: self.execution,
+            state: Conditional(condition),
+            _model: self._model,
+        }
+    }
+}
+
+impl<T: NoSql> DeleteBuilder<T, Conditional> {
+    pub fn serial_consistency(mut self, serial_consistency: SerialConsistency) -> Self {
+        self.state.0.serial_consistency = Some(serial_consistency);
+        self
+    }
+
+    pub fn build(self) -> Lwt<T> {
+        let (consistency, execution) = (self.consistency, self.execution.clone());
+        let (binds, query_string, Conditional(condition)) = self.into_query();
+        condition
+            .build(binds, &query_string)
+            .with_consistency(consistency)
+            .with_execution(execution)
+    }
+}
+
+pub struct BatchBuilder {
+    batch_type: BatchType,
+    statements: Vec<(QueryString, CqlMap)>,
+    // first query that couldn't be added, returned by `build`
+    error: Option<QueryError>,
+    timestamp: Option<i64>,
+    consistency: Option<Consistency>,
+    execution: Execution,
+}
+
+impl BatchBuilder {
+    pub fn new(batch_type: BatchType) -> Self {
+        Self {
+            batch_type,
+            statements: Vec::new(),
+            error: None,
+            timestamp: None,
+            consistency: None,
+            execution: Execution::default(),
+        }
+    }
+
+    pub fn logged() -> Self {
+        Self::new(BatchType::Logged)
+    }
+
+    pub fn unlogged() -> Self {
+        Self::new(BatchType::Unlogged)
+    }
+
+    pub fn counter() -> Self {
+        Self::new(BatchType::Counter)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, query: impl Batchable) -> Self {
+        match query.into_batch_statement() {
+            Ok(statement) => self.statements.push(statement),
+            Err(error) => {
+                self.error.get_or_insert(error);
+            }
+        }
+        self
+    }
+
+    // write time of every statement in the batch, in microseconds since epoch
+    pub fn timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    // consistency of the whole batch, the consistency of the added queries is ignored
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    pub fn build(self) -> Result<Batch, QueryError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        Ok(Batch::create_query(self.batch_type, self.statements, self.timestamp)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution))
+    }
+}
+
+pub struct ScanBuilder<T: NoSql> {
+    ranges: usize,
+    concurrency: usize,
+    page_size: Option<NonZeroU32>,
+    checkpoint: Option<Checkpoint>,
+    consistency: Option<Consistency>,
+    execution: Execution,
+    _model: PhantomData<T>,
+}
+
+impl<T: NoSql> Default for ScanBuilder<T> {
+    fn default() -> Self {
+        Self {
+            ranges: 64,
+            concurrency: 8,
+            page_size: None,
+            checkpoint: None,
+            consistency: T::consistency(),
+            execution: Execution::read(),
+            _model: PhantomData,
+        }
+    }
+}
+
+impl<T: NoSql> ScanBuilder<T> {
+    // number of token ranges the ring is split into
+    pub fn ranges(mut self, ranges: usize) -> Self {
+        self.ranges = ranges;
+        self
+    }
+
+    // token ranges read at the same time
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn page_size(mut self, page_size: NonZeroU32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    // skips the ranges completed by a previous scan, `build` fails when the previous
+    // scan was split into a different number of ranges
+    pub fn from_checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    pub fn build(self) -> Result<Scan<T>, QueryError> {
+        if let Some(checkpoint) = &self.checkpoint {
+            checkpoint.check(self.ranges)?;
+        }
+        let partition_keys = T::schema()
+            .partition_keys()
+            .iter()
+            .map(|column| column.name)
+            .collect::<Vec<&str>>()
+            .join(", ");
         let query_string = format!(
-            "UPDATE {}.{} SET {} WHERE {}",
+            "SELECT * FROM {}.{} WHERE token({}) > :token_start AND token({}) <= :token_end",
             T::keyspace(),
             T::table_name(),
-            self.set_clause.0,
-            filter.query_string
+            partition_keys,
+            partition_keys
+        );
+
+        let queries = TokenRange::split(self.ranges)
+            .into_iter()
+            .filter(|range| {
+                self.checkpoint
+                    .as_ref()
+                    .map_or(true, |checkpoint| !checkpoint.is_complete(range))
+            })
+            .map(|range| {
+                let binds = CqlMap::from([
+                    ("token_start".to_string(), CqlType::NumInt(range.start)),
+                    ("token_end".to_string(), CqlType::NumInt(range.end)),
+                ]);
+                let query = FindAll::<T>::create_query(binds, query_string.clone())
+                    .with_page_size(self.page_size)
+                    .with_consistency(self.consistency)
+                    .with_execution(self.execution.clone());
+                (range, query)
+            })
+            .collect();
+        Ok(Scan::<T>::create_query(queries, self.concurrency))
+    }
+}
+
+// rows are looked up by their whole primary key
+pub struct GetManyBuilder<T: NoSql> {
+    keys: Vec<T::PrimaryKey>,
+    concurrency: usize,
+    consistency: Option<Consistency>,
+    execution: Execution,
+}
+
+impl<T: NoSql> GetManyBuilder<T> {
+    pub fn new(keys: Vec<T::PrimaryKey>) -> Self {
+        Self {
+            keys,
+            concurrency: 16,
+            consistency: T::consistency(),
+            execution: Execution::read(),
+        }
+    }
+
+    // queries in flight at the same time
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    pub fn build(self) -> GetMany<T> {
+        let (consistency, execution) = (self.consistency, self.execution);
+        let queries = self
+            .keys
+            .into_iter()
+            .map(|key| {
+                SelectBuilder::<T, Init>::default()
+                    .filter_by(key.into())
+                    .build()
+                    .with_consistency(consistency)
+                    .with_execution(execution.clone())
+            })
+            .collect();
+        GetMany::<T>::create_query(queries, self.concurrency)
+    }
+}
+
+pub struct AggregateBuilder<T: NoSql, O: FromCqlData> {
+    selector: String,
+    wh_clause: Option<FilterBy<T>>,
+    consistency: Option<Consistency>,
+    execution: Execution,
+    _output: PhantomData<fn() -> O>,
+}
+
+// aggregates without a filter run over the whole table
+impl<T: NoSql, O: FromCqlData> AggregateBuilder<T, O> {
+    pub fn new(selector: String) -> Self {
+        Self {
+            selector,
+            wh_clause: None,
+            consistency: T::consistency(),
+            execution: Execution::read(),
+            _output: PhantomData,
+        }
+    }
+
+    pub fn filter_by(mut self, filter: FilterBy<T>) -> Self {
+        self.wh_clause = Some(filter);
+        self
+    }
+
+    pub fn consistency(mut self, consistency: Consistency) -> Self {
+        self.consistency = Some(consistency);
+        self
+    }
+
+    pub fn build(self) -> Aggregate<T, O> {
+        let mut query_string = format!(
+            "SELECT {} AS value FROM {}.{}",
+            self.selector,
+            T::keyspace(),
+            T::table_name()
+        );
+        let mut binds = CqlMap::new();
+        if let Some(filter) = self.wh_clause {
+            query_string.push_str(&format!(" WHERE {}", filter.query_string()));
+            binds = filter.filter;
+        }
+        Aggregate::<T, O>::create_query(binds, query_string)
+            .with_consistency(self.consistency)
+            .with_execution(self.execution)
+    }
+}
+
+// `timeout`, `idempotent` and `retry` of the built query, for builders with an `execution` field
+macro_rules! impl_execution_options {
+    ($builder:ident $(<$($param:ident: $bound:ident),*>)?) => {
+        impl$(<$($param: $bound),*>)? $builder$(<$($param),*>)? {
+            // bounds every attempt, retries get the same timeout
+            pub fn timeout(mut self, timeout: Duration) -> Self {
+                self.execution = self.execution.timeout(timeout);
+                self
+            }
+
+            // reads are always idempotent, writes have to be marked to be retried by
+            // `RetryPolicy::idempotent_only`
+            pub fn idempotent(mut self, idempotent: bool) -> Self {
+                self.execution = self.execution.idempotent(idempotent);
+                self
+            }
+
+            pub fn retry(mut self, retry: RetryPolicy) -> Self {
+                self.execution = self.execution.retry(retry);
+                self
+            }
+        }
+    };
+}
+
+impl_execution_options!(InsertBuilder<T: NoSql, S: State>);
+impl_execution_options!(SelectBuilder<T: NoSql, S: State>);
+impl_execution_options!(SelectAllBuilder<T: NoSql, S: State, R: Restriction>);
+impl_execution_options!(UpdateBuilder<T: NoSql, S: State, U: Updateable>);
+impl_execution_options!(DeleteBuilder<T: NoSql, S: State>);
+impl_execution_options!(BatchBuilder);
+impl_execution_options!(ScanBuilder<T: NoSql>);
+impl_execution_options!(GetManyBuilder<T: NoSql>);
+impl_execution_options!(AggregateBuilder<T: NoSql, O: FromCqlData>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::column::Partition;
+
+    #[test]
+    fn and_renames_clashing_binds() {
+        let id = Col::<(), i64, Partition>::new("id");
+        let amount = Col::<(), i64, Regular>::new("amount");
+
+        let filter = id.eq(1).and(amount.gt(2)).and(amount.gt(3)).and(id.eq(4));
+
+        assert_eq!(
+            filter.query_string(),
+            "id = ? AND amount > :amount_gt AND amount > :amount_gt_1 AND id = :id_1"
         );
-        Update::<T>::create_query(filter.filter, self.set_clause.1, query_string)
+        let bound = |bind: &str| filter.filter.get(bind).map(i64::from_cql);
+        assert_eq!(bound("id"), Some(Ok(1)));
+        assert_eq!(bound("amount_gt"), Some(Ok(2)));
+        assert_eq!(bound("amount_gt_1"), Some(Ok(3)));
+        assert_eq!(bound("id_1"), Some(Ok(4)));
     }
 }
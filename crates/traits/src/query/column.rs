@@ -0,0 +1,167 @@
+use crate::{
+    data_types::types::{CqlType, ToCqlData},
+    query::client::{FilterBy, Relation},
+};
+use std::{collections::HashMap, marker::PhantomData};
+
+// restriction of a filter, key only filters are `Restricted`, filters only on regular
+// columns are `NonKey` and filters mixing both are `Filtering`, only `NonKey` filters
+// can be the IF clause of a conditional write
+pub struct Restricted;
+pub struct NonKey;
+pub struct Filtering;
+
+pub trait Restriction {
+    const ALLOW_FILTERING: bool;
+}
+
+impl Restriction for Restricted {
+    const ALLOW_FILTERING: bool = false;
+}
+
+impl Restriction for NonKey {
+    const ALLOW_FILTERING: bool = true;
+}
+
+impl Restriction for Filtering {
+    const ALLOW_FILTERING: bool = true;
+}
+
+// restriction of two filters joined with AND
+pub trait Combine<R: Restriction>: Restriction {
+    type Output: Restriction;
+}
+
+impl Combine<Self> for Restricted {
+    type Output = Self;
+}
+
+impl Combine<NonKey> for Restricted {
+    type Output = Filtering;
+}
+
+impl Combine<Filtering> for Restricted {
+    type Output = Filtering;
+}
+
+impl Combine<Restricted> for NonKey {
+    type Output = Filtering;
+}
+
+impl Combine<Self> for NonKey {
+    type Output = Self;
+}
+
+impl Combine<Filtering> for NonKey {
+    type Output = Filtering;
+}
+
+impl<R: Restriction> Combine<R> for Filtering {
+    type Output = Self;
+}
+
+// column kinds
+pub struct Partition;
+pub struct Clustering;
+pub struct Regular;
+
+pub trait ColKind {
+    type Restriction: Restriction;
+}
+
+impl ColKind for Partition {
+    type Restriction = Restricted;
+}
+
+impl ColKind for Clustering {
+    type Restriction = Restricted;
+}
+
+impl ColKind for Regular {
+    type Restriction = NonKey;
+}
+
+// typed handle of a column of model `T` holding values of type `V`, generated per model
+// through `Model::cols()`
+pub struct Col<T, V, K> {
+    name: &'static str,
+    #[allow(clippy::type_complexity)]
+    _model: PhantomData<fn() -> (T, V, K)>,
+}
+
+impl<T, V, K> Clone for Col<T, V, K> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, V, K> Copy for Col<T, V, K> {}
+
+impl<T, V, K> Col<T, V, K> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _model: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T, V: ToCqlData, K: ColKind> Col<T, V, K> {
+    fn predicate(
+        self,
+        operator: &'static str,
+        bind_name: String,
+        value: V,
+    ) -> FilterBy<T, K::Restriction> {
+        let relation = Relation::named(self.name, operator, bind_name.clone());
+        let filter = HashMap::from([(bind_name, value.to_cql())]);
+        FilterBy::new(filter, vec![relation])
+    }
+
+    pub fn eq(self, value: impl Into<V>) -> FilterBy<T, K::Restriction> {
+        let filter = HashMap::from([(self.name.to_string(), value.into().to_cql())]);
+        FilterBy::new(filter, vec![Relation::eq(self.name)])
+    }
+
+    // bound as a single list
+    pub fn is_in(self, values: impl IntoIterator<Item = impl Into<V>>) -> FilterBy<T, K::Restriction> {
+        let bind_name = format!("{}_in", self.name);
+        let relation = Relation::named(self.name, "IN", bind_name.clone());
+        let values = values
+            .into_iter()
+            .map(|value| value.into().to_cql())
+            .collect();
+        let filter = HashMap::from([(bind_name, CqlType::List(values))]);
+        FilterBy::new(filter, vec![relation])
+    }
+}
+
+// partition keys can only be restricted by equality
+macro_rules! impl_range_predicates {
+    ($kind:ty) => {
+        impl<T, V: ToCqlData> Col<T, V, $kind> {
+            pub fn gt(self, value: impl Into<V>) -> FilterBy<T, <$kind as ColKind>::Restriction> {
+                self.predicate(">", format!("{}_gt", self.name), value.into())
+            }
+
+            pub fn gte(self, value: impl Into<V>) -> FilterBy<T, <$kind as ColKind>::Restriction> {
+                self.predicate(">=", format!("{}_gte", self.name), value.into())
+            }
+
+            pub fn lt(self, value: impl Into<V>) -> FilterBy<T, <$kind as ColKind>::Restriction> {
+                self.predicate("<", format!("{}_lt", self.name), value.into())
+            }
+
+            pub fn lte(self, value: impl Into<V>) -> FilterBy<T, <$kind as ColKind>::Restriction> {
+                self.predicate("<=", format!("{}_lte", self.name), value.into())
+            }
+        }
+    };
+}
+
+impl_range_predicates!(Clustering);
+impl_range_predicates!(Regular);
@@ -0,0 +1,238 @@
+use crate::{data_types::types::Consistency, query::query::QueryError};
+use std::{future::Future, time::Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backoff {
+    Fixed(Duration),
+    // doubles after every attempt, up to `max`
+    Exponential { base: Duration, max: Duration },
+}
+
+// how failed queries are retried, queries are not retried by default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    retries: u32,
+    backoff: Backoff,
+    idempotent_only: bool,
+    downgrade_consistency: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+impl RetryPolicy {
+    pub fn never() -> Self {
+        Self::fixed(0, Duration::ZERO)
+    }
+
+    pub fn fixed(retries: u32, delay: Duration) -> Self {
+        Self {
+            retries,
+            backoff: Backoff::Fixed(delay),
+            idempotent_only: false,
+            downgrade_consistency: false,
+        }
+    }
+
+    pub fn exponential(retries: u32, base: Duration, max: Duration) -> Self {
+        Self {
+            retries,
+            backoff: Backoff::Exponential { base, max },
+            idempotent_only: false,
+            downgrade_consistency: false,
+        }
+    }
+
+    // statements that are not marked idempotent are never retried
+    pub fn idempotent_only(mut self) -> Self {
+        self.idempotent_only = true;
+        self
+    }
+
+    // retries of unavailable and server timeout errors use a lower consistency
+    pub fn downgrading_consistency(mut self) -> Self {
+        self.downgrade_consistency = true;
+        self
+    }
+
+    // delay before the retry after `attempt` failed attempts, `None` if the error is final
+    fn delay(&self, attempt: u32, error: &QueryError, idempotent: bool) -> Option<Duration> {
+        if attempt >= self.retries || (self.idempotent_only && !idempotent) {
+            return None;
+        }
+        if !matches!(
+            error,
+            QueryError::Timeout
+                | QueryError::ServerTimeout
+                | QueryError::Unavailable
+                | QueryError::Overloaded
+        ) {
+            return None;
+        }
+        Some(match self.backoff {
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { base, max } => base
+                .checked_mul(2u32.saturating_pow(attempt))
+                .map_or(max, |delay| delay.min(max)),
+        })
+    }
+
+    fn consistency(
+        &self,
+        error: &QueryError,
+        consistency: Option<Consistency>,
+    ) -> Option<Consistency> {
+        let downgrade = self.downgrade_consistency
+            && matches!(error, QueryError::Unavailable | QueryError::ServerTimeout);
+        match consistency {
+            Some(consistency) if downgrade => Some(consistency.downgrade()),
+            consistency => consistency,
+        }
+    }
+}
+
+impl Consistency {
+    // next lower level, levels that can't be lowered are kept
+    fn downgrade(self) -> Self {
+        match self {
+            Self::All => Self::Quorum,
+            Self::Quorum | Self::Two => Self::One,
+            Self::Three => Self::Two,
+            Self::EachQuorum => Self::LocalQuorum,
+            Self::LocalQuorum => Self::LocalOne,
+            other => other,
+        }
+    }
+}
+
+// timeout, idempotence and retry policy of a query
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Execution {
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) idempotent: bool,
+    pub(crate) retry: RetryPolicy,
+}
+
+impl Execution {
+    // reads can always be retried
+    pub(crate) fn read() -> Self {
+        Self::default().idempotent(true)
+    }
+
+    // bounds every attempt, retries get the same timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // idempotent statements can be applied more than once without changing the result
+    pub fn idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = idempotent;
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+}
+
+// attempts of a single query, used by the stores as
+// `loop { match attempts.timed(..).await { Err(e) if attempts.retry(&e).await => continue, .. } }`
+pub(crate) struct Attempts<'a> {
+    execution: &'a Execution,
+    attempt: u32,
+    consistency: Option<Consistency>,
+}
+
+impl<'a> Attempts<'a> {
+    pub(crate) fn new(execution: &'a Execution, consistency: Option<Consistency>) -> Self {
+        Self {
+            execution,
+            attempt: 0,
+            consistency,
+        }
+    }
+
+    // consistency of the next attempt
+    pub(crate) fn consistency(&self) -> Option<Consistency> {
+        self.consistency
+    }
+
+    pub(crate) async fn timed<O>(
+        &self,
+        attempt: impl Future<Output = Result<O, QueryError>>,
+    ) -> Result<O, QueryError> {
+        match self.execution.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, attempt)
+                .await
+                .unwrap_or(Err(QueryError::Timeout)),
+            None => attempt.await,
+        }
+    }
+
+    // waits for the backoff of the policy, false if the query shouldn't be retried
+    pub(crate) async fn retry(&mut self, error: &QueryError) -> bool {
+        let retry = &self.execution.retry;
+        let Some(delay) = retry.delay(self.attempt, error, self.execution.idempotent) else {
+            return false;
+        };
+        self.consistency = retry.consistency(error, self.consistency);
+        self.attempt += 1;
+        tokio::time::sleep(delay).await;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn never_retries_by_default() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay(0, &QueryError::Timeout, true), None);
+    }
+
+    #[test]
+    fn retries_transient_errors_until_exhausted() {
+        let delay = Duration::from_millis(10);
+        let policy = RetryPolicy::fixed(2, delay);
+        assert_eq!(
+            policy.delay(0, &QueryError::Unavailable, false),
+            Some(delay)
+        );
+        assert_eq!(policy.delay(1, &QueryError::Overloaded, false), Some(delay));
+        assert_eq!(policy.delay(2, &QueryError::Timeout, false), None);
+        assert_eq!(policy.delay(0, &QueryError::Decode, false), None);
+    }
+
+    #[test]
+    fn skips_writes_that_are_not_idempotent() {
+        let policy = RetryPolicy::fixed(1, Duration::ZERO).idempotent_only();
+        assert_eq!(policy.delay(0, &QueryError::ServerTimeout, false), None);
+        assert_eq!(
+            policy.delay(0, &QueryError::ServerTimeout, true),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn doubles_delay_up_to_max() {
+        let (base, max) = (Duration::from_millis(50), Duration::from_millis(150));
+        let policy = RetryPolicy::exponential(u32::MAX, base, max);
+        let delays = [0, 1, 2, 64].map(|attempt| policy.delay(attempt, &QueryError::Timeout, true));
+        assert_eq!(
+            delays,
+            [
+                Some(base),
+                Some(Duration::from_millis(100)),
+                Some(max),
+                Some(max)
+            ]
+        );
+    }
+}
@@ -1,2 +1,7 @@
 pub mod client;
+pub mod column;
+pub mod execution;
+pub mod page;
+#[allow(clippy::module_inception)]
 pub mod query;
+pub mod scan;
@@ -0,0 +1,193 @@
+use crate::{
+    data_types::types::CqlMap,
+    nosql::interface::NoSql,
+    query::query::{FindAll, QueryError, QueryResultType},
+};
+use std::{fmt, num::NonZeroU32, str::FromStr};
+
+// opaque position in the results of a query, handed out to api clients as a
+// hex string made of the query fingerprint followed by the driver paging state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    fingerprint: u64,
+    paging_state: Vec<u8>,
+}
+
+impl Cursor {
+    pub(crate) fn new(fingerprint: u64, paging_state: Vec<u8>) -> Self {
+        Self {
+            fingerprint,
+            paging_state,
+        }
+    }
+
+    // paging state if the cursor was handed out for the same query
+    pub(crate) fn into_paging_state(self, fingerprint: u64) -> Result<Vec<u8>, QueryError> {
+        if self.fingerprint != fingerprint {
+            return Err(QueryError::InvalidCursor);
+        }
+        Ok(self.paging_state)
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.fingerprint)?;
+        for byte in self.paging_state.iter() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() < 16 || s.len() % 2 != 0 || !s.is_ascii() {
+            return Err(QueryError::InvalidCursor);
+        }
+        let (fingerprint, paging_state) = s.split_at(16);
+        let fingerprint =
+            u64::from_str_radix(fingerprint, 16).map_err(|_| QueryError::InvalidCursor)?;
+        let paging_state = paging_state
+            .as_bytes()
+            .chunks(2)
+            .map(|byte| {
+                std::str::from_utf8(byte)
+                    .ok()
+                    .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+                    .ok_or(QueryError::InvalidCursor)
+            })
+            .collect::<Result<Vec<u8>, QueryError>>()?;
+        Ok(Self::new(fingerprint, paging_state))
+    }
+}
+
+// FNV-1a, unlike `DefaultHasher` it is stable across processes and releases
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+// identifies a query with its bound values, a paging state is only valid for
+// the query that produced it
+pub(crate) fn fingerprint(query: &str, binds: &CqlMap) -> u64 {
+    let mut binds = binds.iter().collect::<Vec<_>>();
+    binds.sort_by_key(|(left, _)| *left);
+
+    let mut hash = fnv1a(0xcbf2_9ce4_8422_2325, query.as_bytes());
+    for (key, value) in binds {
+        hash = fnv1a(hash, key.as_bytes());
+        hash = fnv1a(hash, format!("{:?}", value).as_bytes());
+    }
+    hash
+}
+
+// page size as the drivers take it, pages larger than `i32::MAX` rows are capped
+pub(crate) fn driver_page_size(page_size: NonZeroU32) -> i32 {
+    i32::try_from(page_size.get()).unwrap_or(i32::MAX)
+}
+
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    // `None` on the last page
+    pub next: Option<Cursor>,
+}
+
+// single page of a FindAll query, starting at `cursor`
+pub struct FindPage<T: NoSql> {
+    pub(crate) query: FindAll<T>,
+    pub(crate) page_size: NonZeroU32,
+    pub(crate) cursor: Option<Cursor>,
+}
+
+impl<T: NoSql> FindPage<T> {
+    pub fn create_query(query: FindAll<T>, page_size: NonZeroU32, cursor: Option<Cursor>) -> Self {
+        Self {
+            query,
+            page_size,
+            cursor,
+        }
+    }
+
+    pub(crate) fn fingerprint(&self) -> u64 {
+        fingerprint(&self.query.query, &self.query.binds)
+    }
+
+    // paging state to resume from, empty for the first page
+    pub(crate) fn paging_state(&mut self) -> Result<Option<Vec<u8>>, QueryError> {
+        let fingerprint = self.fingerprint();
+        self.cursor
+            .take()
+            .map(|cursor| cursor.into_paging_state(fingerprint))
+            .transpose()
+    }
+}
+
+impl<T: NoSql> QueryResultType for FindPage<T> {
+    type Output = Page<T>;
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+    use crate::data_types::types::CqlType;
+
+    #[test]
+    fn cursor_round_trips() -> Result<(), QueryError> {
+        let cursor = Cursor::new(0x0123_4567_89ab_cdef, vec![0x00, 0x0a, 0xff]);
+        let encoded = cursor.to_string();
+
+        assert_eq!(encoded, "0123456789abcdef000aff");
+        assert_eq!(encoded.parse::<Cursor>()?, cursor);
+        assert_eq!(
+            "00000000000000ff".parse::<Cursor>()?,
+            Cursor::new(0xff, Vec::new())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malformed_cursors() {
+        for cursor in [
+            "",
+            "0123",
+            "0123456789abcdef0",
+            "0123456789abcdefzz",
+            // even length, `é` straddles the end of the fingerprint
+            "0123456789abcdeé0",
+        ] {
+            assert!(
+                matches!(cursor.parse::<Cursor>(), Err(QueryError::InvalidCursor)),
+                "{cursor}"
+            );
+        }
+    }
+
+    #[test]
+    fn cursor_belongs_to_its_query() -> Result<(), QueryError> {
+        let binds = CqlMap::from([
+            ("id".to_string(), CqlType::NumInt(1)),
+            ("status".to_string(), CqlType::Str("ok".to_string())),
+        ]);
+        let query = "SELECT * FROM test.payments WHERE id = ? AND status = ?";
+        let query_fingerprint = fingerprint(query, &binds);
+
+        let other_binds = CqlMap::from([("id".to_string(), CqlType::NumInt(2))]);
+        assert_ne!(query_fingerprint, fingerprint(query, &other_binds));
+
+        let cursor = Cursor::new(query_fingerprint, vec![1, 2]);
+        assert!(matches!(
+            cursor
+                .clone()
+                .into_paging_state(query_fingerprint.wrapping_add(1)),
+            Err(QueryError::InvalidCursor)
+        ));
+        assert_eq!(cursor.into_paging_state(query_fingerprint)?, vec![1, 2]);
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
-use crate::data_types::types::CqlMap;
+use crate::data_types::types::{
+    Consistency, CqlMap, CqlMapWithQuery, CqlType, FromCqlData, QueryString, SerialConsistency,
+    ToCqlRow,
+};
 use crate::nosql::interface::{CqlStore, NoSql};
-use std::marker::PhantomData;
+use crate::query::execution::Execution;
+use futures::{stream::BoxStream, StreamExt, TryStreamExt};
+use std::{marker::PhantomData, num::NonZeroU32};
 
 pub trait QueryResultType {
     type Output;
@@ -14,6 +19,40 @@ pub enum QueryError {
     E02,
     #[error("E03")]
     E03,
+    // a row returned by the store couldn't be converted into the model
+    #[error("row decode failed")]
+    Decode,
+    // a model couldn't be converted into the binds of a row
+    #[error("row encode failed")]
+    Encode,
+    // no row matched the filter of a `FindOne`
+    #[error("row not found")]
+    NotFound,
+    // cursor is malformed or was handed out for another query
+    #[error("invalid cursor")]
+    InvalidCursor,
+    #[error("invalid checkpoint")]
+    InvalidCheckpoint,
+    // an update without any field set
+    #[error("nothing to update")]
+    EmptyUpdate,
+    // the query didn't complete within its timeout
+    #[error("query timed out")]
+    Timeout,
+    // replicas didn't answer within the timeout of the server
+    #[error("server timed out")]
+    ServerTimeout,
+    // not enough replicas are alive to reach the consistency
+    #[error("not enough replicas available")]
+    Unavailable,
+    #[error("server overloaded")]
+    Overloaded,
+    // any other error returned by the server
+    #[error("server error: {0}")]
+    Server(String),
+    // connection and protocol errors of the driver
+    #[error("driver error: {0}")]
+    Driver(String),
 }
 
 #[async_trait::async_trait]
@@ -25,20 +64,43 @@ pub trait QueryInterface<S: CqlStore>: QueryResultType {
     fn into_statement(self) -> S::Statement;
 }
 
+// queries whose rows are fetched lazily, one page at a time
+pub trait QueryStream<S: CqlStore>: QueryResultType {
+    type Item;
+    fn stream<'s>(self, store: S) -> BoxStream<'s, Result<Self::Item, QueryError>>
+    where
+        S: 's,
+        Self: 's;
+}
+
 pub struct FindOne<T: NoSql> {
     pub(crate) binds: CqlMap,
-    pub(crate) query: String,
+    pub(crate) query: QueryString,
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
     _model: PhantomData<T>,
 }
 
 impl<T: NoSql> FindOne<T> {
-    pub fn create_query(binds: CqlMap, query: String) -> Self {
+    pub fn create_query(binds: CqlMap, query: impl Into<QueryString>) -> Self {
         Self {
             binds,
-            query,
+            query: query.into(),
+            consistency: T::consistency(),
+            execution: Execution::read(),
             _model: PhantomData,
         }
     }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
 }
 
 impl<T: NoSql> QueryResultType for FindOne<T> {
@@ -47,18 +109,40 @@ impl<T: NoSql> QueryResultType for FindOne<T> {
 
 pub struct FindAll<T: NoSql> {
     pub(crate) binds: CqlMap,
-    pub(crate) query: String,
+    pub(crate) query: QueryString,
+    pub(crate) page_size: Option<NonZeroU32>,
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
     _model: PhantomData<T>,
 }
 
 impl<T: NoSql> FindAll<T> {
-    pub fn create_query(binds: CqlMap, query: String) -> Self {
+    pub fn create_query(binds: CqlMap, query: impl Into<QueryString>) -> Self {
         Self {
-            binds: binds,
-            query: query,
+            binds,
+            query: query.into(),
+            page_size: None,
+            consistency: T::consistency(),
+            execution: Execution::read(),
             _model: PhantomData,
         }
     }
+
+    // rows fetched per round trip when streamed
+    pub fn with_page_size(mut self, page_size: Option<NonZeroU32>) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
 }
 
 impl<T: NoSql> FindOne<T> {
@@ -66,10 +150,12 @@ impl<T: NoSql> FindOne<T> {
         T::keyspace()
     }
 
-    fn create(binds: CqlMap, query: String) -> Self {
+    fn create(binds: CqlMap, query: impl Into<QueryString>) -> Self {
         Self {
-            binds: binds,
-            query: query,
+            binds,
+            query: query.into(),
+            consistency: T::consistency(),
+            execution: Execution::read(),
             _model: PhantomData,
         }
     }
@@ -79,34 +165,204 @@ impl<T: NoSql> QueryResultType for FindAll<T> {
     type Output = Vec<T>;
 }
 
-pub struct Update<T: NoSql> {
-    where_binds: CqlMap,
-    set_binds: CqlMap,
-    query: String,
+// single value computed over the selected rows, aliased as `value`
+pub struct Aggregate<T: NoSql, O: FromCqlData> {
+    pub(crate) binds: CqlMap,
+    pub(crate) query: QueryString,
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
+    _model: PhantomData<fn() -> (T, O)>,
+}
+
+impl<T: NoSql, O: FromCqlData> Aggregate<T, O> {
+    pub fn create_query(binds: CqlMap, query: impl Into<QueryString>) -> Self {
+        Self {
+            binds,
+            query: query.into(),
+            consistency: T::consistency(),
+            execution: Execution::read(),
+            _model: PhantomData,
+        }
+    }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
+
+    pub(crate) fn from_row(mut row: CqlMap) -> Option<O> {
+        O::from_cql(&row.remove("value")?).ok()
+    }
+}
+
+impl<T: NoSql, O: FromCqlData> QueryResultType for Aggregate<T, O> {
+    type Output = O;
+}
+
+// distinct partition keys of a table, only meant to be streamed
+pub struct DistinctKeys<T: NoSql> {
+    pub(crate) query: QueryString,
+    pub(crate) page_size: Option<NonZeroU32>,
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
+    _model: PhantomData<T>,
+}
+
+impl<T: NoSql> DistinctKeys<T> {
+    pub fn create_query(query: impl Into<QueryString>) -> Self {
+        Self {
+            query: query.into(),
+            page_size: None,
+            consistency: T::consistency(),
+            execution: Execution::read(),
+            _model: PhantomData,
+        }
+    }
+
+    // keys fetched per round trip
+    pub fn page_size(mut self, page_size: NonZeroU32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
+}
+
+impl<T: NoSql> QueryResultType for DistinctKeys<T> {
+    type Output = Vec<T::Key>;
+}
+
+// lookups of single rows by primary key, sent as separate queries rather than
+// one IN query so the load is spread across coordinators
+pub struct GetMany<T: NoSql> {
+    queries: Vec<FindOne<T>>,
+    concurrency: usize,
+}
+
+impl<T: NoSql> GetMany<T> {
+    pub fn create_query(queries: Vec<FindOne<T>>, concurrency: usize) -> Self {
+        Self {
+            queries,
+            concurrency,
+        }
+    }
+
+    // results are in the order of the keys, `None` for keys without a row
+    pub async fn execute<S>(self, store: S) -> Result<Vec<Option<T>>, QueryError>
+    where
+        S: CqlStore + Copy,
+        FindOne<T>: QueryInterface<S, Output = T>,
+    {
+        futures::stream::iter(self.queries)
+            .map(|query| async move {
+                match query.execute(store).await {
+                    Ok(row) => Ok(Some(row)),
+                    Err(QueryError::NotFound) => Ok(None),
+                    Err(error) => Err(error),
+                }
+            })
+            .buffered(self.concurrency.max(1))
+            .try_collect()
+            .await
+    }
+}
+
+impl<T: NoSql> QueryResultType for GetMany<T> {
+    type Output = Vec<Option<T>>;
+}
+
+// `U` is the update struct of the model, only its fields that are set are
+// part of the SET clause
+pub struct Update<T: NoSql, U> {
+    pub(crate) where_binds: CqlMap,
+    pub(crate) set: U,
+    pub(crate) query: QueryString,
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
     _model: PhantomData<T>,
 }
 
-impl<T: NoSql> Update<T> {
-    pub fn create_query(where_binds: CqlMap, set_binds: CqlMap, query: String) -> Self {
+impl<T: NoSql, U: ToCqlRow<Output = CqlMapWithQuery>> Update<T, U> {
+    pub fn create_query(where_binds: CqlMap, set: U, query: impl Into<QueryString>) -> Self {
         Self {
             where_binds,
-            set_binds,
-            query,
+            set,
+            query: query.into(),
+            consistency: T::consistency(),
+            execution: Execution::default(),
             _model: PhantomData,
         }
     }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
+
+    // where and set binds share the statement
+    pub(crate) fn into_query(self) -> (QueryString, CqlMap) {
+        let mut binds = self.where_binds;
+        binds.extend(self.set.to_row_iter().flat_map(|(_, set_binds)| set_binds));
+        (self.query, binds)
+    }
 }
-impl<T: NoSql> QueryResultType for Update<T> {
-    type Output = usize;
+// cassandra doesn't report the number of updated rows
+impl<T: NoSql, U> QueryResultType for Update<T, U> {
+    type Output = bool;
 }
 
 pub struct Create<T: NoSql> {
     pub(crate) model: T,
+    pub(crate) query: QueryString,
+    // binds of the USING clause
+    pub(crate) binds: CqlMap,
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
 }
 
 impl<T: NoSql> Create<T> {
     pub fn create_query(model: T) -> Self {
-        Self { model }
+        Self {
+            model,
+            query: T::insert_statement().into(),
+            binds: CqlMap::new(),
+            consistency: T::consistency(),
+            execution: Execution::default(),
+        }
+    }
+
+    pub fn with_options(mut self, query: impl Into<QueryString>, binds: CqlMap) -> Self {
+        self.query = query.into();
+        self.binds = binds;
+        self
+    }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
     }
 }
 
@@ -114,11 +370,174 @@ impl<T: NoSql> QueryResultType for Create<T> {
     type Output = bool;
 }
 
-struct Delete<T: NoSql> {
-    wh_clause: CqlMap,
+pub struct Delete<T: NoSql> {
+    pub(crate) binds: CqlMap,
+    pub(crate) query: QueryString,
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
     _model: PhantomData<T>,
 }
 
+impl<T: NoSql> Delete<T> {
+    pub fn create_query(binds: CqlMap, query: impl Into<QueryString>) -> Self {
+        Self {
+            binds,
+            query: query.into(),
+            consistency: T::consistency(),
+            execution: Execution::default(),
+            _model: PhantomData,
+        }
+    }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
+}
+
 impl<T: NoSql> QueryResultType for Delete<T> {
     type Output = bool;
 }
+
+// conditional write, `IF NOT EXISTS`, `IF EXISTS` or `IF <conditions>`
+pub struct Lwt<T: NoSql> {
+    pub(crate) binds: CqlMap,
+    pub(crate) query: QueryString,
+    pub(crate) serial_consistency: Option<SerialConsistency>,
+    // consistency of the commit phase
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
+    _model: PhantomData<T>,
+}
+
+impl<T: NoSql> Lwt<T> {
+    pub fn create_query(
+        binds: CqlMap,
+        query: impl Into<QueryString>,
+        serial_consistency: Option<SerialConsistency>,
+    ) -> Self {
+        Self {
+            binds,
+            query: query.into(),
+            serial_consistency,
+            consistency: T::consistency(),
+            execution: Execution::default(),
+            _model: PhantomData,
+        }
+    }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
+}
+
+impl<T: NoSql> QueryResultType for Lwt<T> {
+    type Output = LwtResult<T>;
+}
+
+#[derive(Debug)]
+pub struct LwtResult<T> {
+    pub applied: bool,
+    // row as it was when an insert was not applied, `None` for updates and deletes
+    // which only get the columns of their condition back
+    pub current: Option<T>,
+}
+
+impl<T: NoSql> LwtResult<T> {
+    pub(crate) fn from_row(mut row: CqlMap) -> Option<Self> {
+        let applied = match row.remove("[applied]")? {
+            CqlType::Bool(applied) => applied,
+            _ => return None,
+        };
+        let current = match applied {
+            true => None,
+            false => T::from_cql(&CqlType::Row(row)).ok(),
+        };
+        Some(Self { applied, current })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchType {
+    Logged,
+    Unlogged,
+    Counter,
+}
+
+// group of writes sent as a single BATCH, can span models and tables
+pub struct Batch {
+    pub(crate) batch_type: BatchType,
+    pub(crate) statements: Vec<(QueryString, CqlMap)>,
+    pub(crate) timestamp: Option<i64>,
+    // batches can span models, model defaults don't apply
+    pub(crate) consistency: Option<Consistency>,
+    pub(crate) execution: Execution,
+}
+
+impl Batch {
+    pub fn create_query(
+        batch_type: BatchType,
+        statements: Vec<(QueryString, CqlMap)>,
+        timestamp: Option<i64>,
+    ) -> Self {
+        Self {
+            batch_type,
+            statements,
+            timestamp,
+            consistency: None,
+            execution: Execution::default(),
+        }
+    }
+
+    pub fn with_consistency(mut self, consistency: Option<Consistency>) -> Self {
+        self.consistency = consistency;
+        self
+    }
+
+    pub fn with_execution(mut self, execution: Execution) -> Self {
+        self.execution = execution;
+        self
+    }
+}
+
+impl QueryResultType for Batch {
+    type Output = bool;
+}
+
+// queries that can be part of a batch
+pub trait Batchable {
+    fn into_batch_statement(self) -> Result<(QueryString, CqlMap), QueryError>;
+}
+
+impl<T: NoSql> Batchable for Create<T> {
+    fn into_batch_statement(self) -> Result<(QueryString, CqlMap), QueryError> {
+        let CqlType::Row(mut binds) = self.model.to_cql() else {
+            return Err(QueryError::Encode);
+        };
+        binds.extend(self.binds);
+        Ok((self.query, binds))
+    }
+}
+
+impl<T: NoSql, U: ToCqlRow<Output = CqlMapWithQuery>> Batchable for Update<T, U> {
+    fn into_batch_statement(self) -> Result<(QueryString, CqlMap), QueryError> {
+        Ok(self.into_query())
+    }
+}
+
+impl<T: NoSql> Batchable for Delete<T> {
+    fn into_batch_statement(self) -> Result<(QueryString, CqlMap), QueryError> {
+        Ok((self.query, self.binds))
+    }
+}
@@ -0,0 +1,258 @@
+use crate::{
+    nosql::interface::{CqlStore, NoSql},
+    query::query::{FindAll, QueryError, QueryResultType, QueryStream},
+};
+use futures::{stream::BoxStream, StreamExt};
+use std::{collections::BTreeSet, fmt, str::FromStr};
+
+// range of the Murmur3 token ring, `start` is exclusive and `end` inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenRange {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl TokenRange {
+    // bounds of the whole ring, i64::MIN is never a token so the first range can
+    // leave it out
+    pub const RING: Self = Self {
+        start: i64::MIN,
+        end: i64::MAX,
+    };
+
+    // splits the whole ring into `count` ranges of about the same size
+    pub fn split(count: usize) -> Vec<Self> {
+        let count = i128::try_from(count.max(1)).unwrap_or(i128::from(u32::MAX));
+        let (start, end) = (i128::from(Self::RING.start), i128::from(Self::RING.end));
+        let bound = |i: i128| i64::try_from(start + (end - start) * i / count).unwrap_or(i64::MAX);
+        (0..count)
+            .map(|i| Self {
+                start: bound(i),
+                end: bound(i + 1),
+            })
+            .collect()
+    }
+}
+
+// token ranges a scan has finished, a scan built from a checkpoint skips them.
+// The split the ranges come from is recorded, a scan split differently rejects it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    ranges: usize,
+    ring: TokenRange,
+    completed: BTreeSet<TokenRange>,
+}
+
+impl Checkpoint {
+    // checkpoint of a scan split into `ranges` token ranges
+    pub fn new(ranges: usize) -> Self {
+        Self {
+            ranges: ranges.max(1),
+            ring: TokenRange::RING,
+            completed: BTreeSet::new(),
+        }
+    }
+
+    pub(crate) fn check(&self, ranges: usize) -> Result<(), QueryError> {
+        if self.ranges != ranges.max(1) || self.ring != TokenRange::RING {
+            return Err(QueryError::InvalidCheckpoint);
+        }
+        Ok(())
+    }
+
+    pub fn complete(&mut self, range: TokenRange) {
+        self.completed.insert(range);
+    }
+
+    pub fn is_complete(&self, range: &TokenRange) -> bool {
+        self.completed.contains(range)
+    }
+
+    pub fn completed(&self) -> usize {
+        self.completed.len()
+    }
+}
+
+impl fmt::Display for TokenRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.start, self.end)
+    }
+}
+
+impl FromStr for TokenRange {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once(':').ok_or(QueryError::InvalidCheckpoint)?;
+        Ok(Self {
+            start: start.parse().map_err(|_| QueryError::InvalidCheckpoint)?,
+            end: end.parse().map_err(|_| QueryError::InvalidCheckpoint)?,
+        })
+    }
+}
+
+// `{ranges}/{ring}/{completed}` with the completed ranges separated by commas
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let completed = self
+            .completed
+            .iter()
+            .map(TokenRange::to_string)
+            .collect::<Vec<String>>();
+        write!(f, "{}/{}/{}", self.ranges, self.ring, completed.join(","))
+    }
+}
+
+impl FromStr for Checkpoint {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, '/');
+        let (Some(ranges), Some(ring), Some(completed)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(QueryError::InvalidCheckpoint);
+        };
+        let completed = completed
+            .split(',')
+            .filter(|range| !range.is_empty())
+            .map(TokenRange::from_str)
+            .collect::<Result<BTreeSet<TokenRange>, QueryError>>()?;
+        Ok(Self {
+            ranges: ranges.parse().map_err(|_| QueryError::InvalidCheckpoint)?,
+            ring: ring.parse()?,
+            completed,
+        })
+    }
+}
+
+pub enum ScanEvent<T> {
+    Row(T),
+    // every row of the range was yielded, ranges that hit an error are not reported
+    RangeDone(TokenRange),
+}
+
+// full table scan, every token range is read with its own FindAll query
+pub struct Scan<T: NoSql> {
+    pub(crate) queries: Vec<(TokenRange, FindAll<T>)>,
+    pub(crate) concurrency: usize,
+}
+
+impl<T: NoSql> Scan<T> {
+    pub fn create_query(queries: Vec<(TokenRange, FindAll<T>)>, concurrency: usize) -> Self {
+        Self {
+            queries,
+            concurrency,
+        }
+    }
+}
+
+impl<T: NoSql> QueryResultType for Scan<T> {
+    type Output = Vec<T>;
+}
+
+// ranges are streamed `concurrency` at a time and their rows are merged as they come
+impl<S, T> QueryStream<S> for Scan<T>
+where
+    S: CqlStore + Copy + Send,
+    T: NoSql + Send,
+    FindAll<T>: QueryStream<S, Item = T>,
+{
+    type Item = ScanEvent<T>;
+
+    fn stream<'s>(self, store: S) -> BoxStream<'s, Result<Self::Item, QueryError>>
+    where
+        S: 's,
+        Self: 's,
+    {
+        let concurrency = self.concurrency.max(1);
+        futures::stream::iter(self.queries)
+            .map(move |(range, query)| {
+                // a range stops at its first error and is then never reported as done
+                futures::stream::unfold(
+                    (query.stream(store), Some(range)),
+                    |(mut rows, range)| async move {
+                        let range = range?;
+                        match rows.next().await {
+                            Some(Ok(row)) => Some((Ok(ScanEvent::Row(row)), (rows, Some(range)))),
+                            Some(Err(error)) => Some((Err(error), (rows, None))),
+                            None => Some((Ok(ScanEvent::RangeDone(range)), (rows, None))),
+                        }
+                    },
+                )
+                .boxed()
+            })
+            .flatten_unordered(concurrency)
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::panic_in_result_fn)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_the_whole_ring() {
+        for count in [1, 2, 3, 7, 64, 1000] {
+            let ranges = TokenRange::split(count);
+
+            assert_eq!(ranges.len(), count);
+            assert_eq!(ranges.first().map(|range| range.start), Some(i64::MIN));
+            assert_eq!(ranges.last().map(|range| range.end), Some(i64::MAX));
+            assert!(ranges.windows(2).all(|pair| match pair {
+                [left, right] => left.end == right.start && left.start < left.end,
+                _ => false,
+            }));
+        }
+        assert_eq!(TokenRange::split(0), vec![TokenRange::RING]);
+    }
+
+    #[test]
+    fn checkpoint_round_trips() -> Result<(), QueryError> {
+        let ranges = TokenRange::split(4);
+        let mut checkpoint = Checkpoint::new(4);
+        for range in ranges.iter().skip(1) {
+            checkpoint.complete(*range);
+        }
+        let resumed = checkpoint.to_string().parse::<Checkpoint>()?;
+
+        assert_eq!(resumed, checkpoint);
+        assert_eq!(resumed.completed(), 3);
+        assert!(ranges
+            .iter()
+            .map(|range| resumed.is_complete(range))
+            .eq([false, true, true, true]));
+        assert_eq!(
+            Checkpoint::new(2).to_string(),
+            "2/-9223372036854775808:9223372036854775807/"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_checkpoints_of_other_splits() -> Result<(), QueryError> {
+        let checkpoint = Checkpoint::new(64);
+
+        assert!(checkpoint.check(64).is_ok());
+        assert!(matches!(
+            checkpoint.check(128),
+            Err(QueryError::InvalidCheckpoint)
+        ));
+        let other_ring = "64/0:9223372036854775807/".parse::<Checkpoint>()?;
+        assert!(matches!(
+            other_ring.check(64),
+            Err(QueryError::InvalidCheckpoint)
+        ));
+        for checkpoint in ["", "64", "64/0:1", "x/0:1/", "64/0:1/2"] {
+            assert!(
+                matches!(
+                    checkpoint.parse::<Checkpoint>(),
+                    Err(QueryError::InvalidCheckpoint)
+                ),
+                "{checkpoint}"
+            );
+        }
+        Ok(())
+    }
+}
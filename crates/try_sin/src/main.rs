@@ -1,12 +1,8 @@
+// scratch binary for trying out models, most of it is commented out
+#![allow(dead_code)]
+
 use stargate_grpc::*;
-use std::collections::HashMap;
 use std::str::FromStr;
-use traits::query::client::*;
-use traits::query::query::QueryInterface;
-use traits::{
-    data_types::types::{CqlMapWithQuery, CqlType, FromCqlData, ToCqlData, ToCqlRow},
-    nosql::interface::NoSql,
-};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -21,8 +17,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     //     .filter_by(Payment::filter_by_id(1i64))
     //     .build();
 
-    // let update_payment = UpdatePayment {
-    //     status: "asdasd".to_string(),
+    // let update_payment = PaymentUpdate {
+    //     amount: Some(10.0),
     // };
     // let _update_query = update_payment
     //     .update()
@@ -58,21 +54,3 @@ struct Payment {
     status: String,
     amount: f64,
 }
-
-struct UpdatePayment {
-    status: String,
-}
-
-impl Updateable for UpdatePayment {
-    type ParentModel = Payment;
-}
-
-impl ToCqlRow for UpdatePayment {
-    type Output = CqlMapWithQuery;
-
-    fn to_row_iter(self) -> impl Iterator<Item = Self::Output> {
-        let set_clause_q = "status = ?";
-        let set_clause = HashMap::from([("status".to_string(), self.status.to_cql())]);
-        std::iter::once((set_clause_q.to_string(), set_clause))
-    }
-}